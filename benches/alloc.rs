@@ -0,0 +1,52 @@
+//! Allocations of every solver on generated inputs. Run with
+//! `cargo bench --features alloc_profile --bench alloc`.
+
+use aoc23::{
+    generators::{self, rng},
+    profile::profile,
+    solutions::solver,
+};
+
+fn inputs(day: u32) -> [String; 2] {
+    let rng = &mut rng(day as u64);
+    let input = match day {
+        1 => generators::day_01::generate(rng, &Default::default()),
+        2 => generators::day_02::generate(rng, &Default::default()),
+        3 => generators::day_03::generate(rng, &Default::default()),
+        4 => generators::day_04::generate(rng, &Default::default()),
+        5 => generators::day_05::generate(rng, &Default::default()),
+        6 => generators::day_06::generate(rng, &Default::default()),
+        7 => generators::day_07::generate(rng, &Default::default()),
+        8 => {
+            let input = generators::day_08::generate(rng, &Default::default());
+            return [
+                input,
+                generators::day_08::generate_ghosts(rng, &Default::default()),
+            ];
+        }
+        9 => generators::day_09::generate(rng, &Default::default()),
+        10 => generators::day_10::generate(rng, &Default::default()),
+        _ => unreachable!(),
+    };
+    [input.clone(), input]
+}
+
+fn main() {
+    println!(
+        "{:>3} {:>4} {:>12} {:>14} {:>14}",
+        "day", "part", "allocations", "bytes", "peak"
+    );
+    for day in 1..=10 {
+        for (part, input) in (1..).zip(inputs(day)) {
+            let Some(solver) = solver(day, part) else {
+                continue;
+            };
+            let (answer, stats) = profile(solver, &input);
+            answer.unwrap_or_else(|e| panic!("Day {day} part {part}: {e:#}"));
+            println!(
+                "{day:>3} {part:>4} {:>12} {:>14} {:>14}",
+                stats.allocations, stats.bytes, stats.peak
+            );
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use aoc23::{
+    day_07,
+    generators::{day_07::generate, day_07::Params, rng},
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+fn rank_hands(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day_07");
+    for hands in [1_000, 100_000, 1_000_000] {
+        let params = Params {
+            hands,
+            ..Default::default()
+        };
+        let input = generate(&mut rng(7), &params);
+
+        group.throughput(Throughput::Elements(hands as u64));
+        group.bench_with_input(BenchmarkId::new("problem_1", hands), &input, |b, input| {
+            b.iter(|| day_07::problem_1::solve(input).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = rank_hands
+}
+criterion_main!(benches);
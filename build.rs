@@ -0,0 +1,91 @@
+//! Turns `resources/manifest.txt` into one test per line, included by
+//! `src/manifest.rs`. Parts without an arm in `src/solutions.rs` yet are
+//! generated ignored, so they show up as skipped rather than passed, and so
+//! are inputs whose answer is still `?` and encrypted inputs when there is no
+//! key. Tests of other encrypted inputs check the `input_key` cfg set here.
+
+use std::{collections::HashSet, env, fmt::Write, fs, path::Path};
+
+const MANIFEST: &str = "resources/manifest.txt";
+const SOLUTIONS: &str = "src/solutions.rs";
+// As in `src/store.rs`.
+const KEY_VAR: &str = "AOC_INPUT_KEY";
+const KEY_FILE: &str = ".aoc-input-key";
+
+fn main() {
+    println!("cargo:rerun-if-changed={MANIFEST}");
+    println!("cargo:rerun-if-changed={SOLUTIONS}");
+    println!("cargo:rerun-if-env-changed={KEY_VAR}");
+    // Watching a file that doesn't exist reruns this on every build, so a key
+    // file added later only counts once this file or the manifest changes.
+    if Path::new(KEY_FILE).exists() {
+        println!("cargo:rerun-if-changed={KEY_FILE}");
+    }
+
+    println!("cargo:rustc-check-cfg=cfg(input_key)");
+    let key = env::var_os(KEY_VAR).is_some() || Path::new(KEY_FILE).exists();
+    if key {
+        println!("cargo:rustc-cfg=input_key");
+    }
+
+    let manifest = fs::read_to_string(MANIFEST).expect("reading the test manifest");
+    let solutions = fs::read_to_string(SOLUTIONS).expect("reading the solvers");
+    let mut names = HashSet::new();
+    let mut tests = String::new();
+    for (number, line) in manifest.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<_> = line.split_whitespace().collect();
+        let [day, part, input, expected] = fields[..] else {
+            panic!(
+                "{MANIFEST}:{}: expected `day part input expected`",
+                number + 1
+            );
+        };
+        let (Ok(day), Ok(part)) = (day.parse::<u32>(), part.parse::<u32>()) else {
+            panic!("{MANIFEST}:{}: day and part must be numbers", number + 1);
+        };
+
+        let stem = input.strip_suffix(".inp").unwrap_or(input);
+        let stem: String = stem
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let name = format!("day_{day:02}_part_{part}_{stem}");
+        if !names.insert(name.clone()) {
+            panic!("{MANIFEST}:{}: {name} is listed twice", number + 1);
+        }
+
+        if !solutions.contains(&format!("({day}, {part}) =>")) {
+            writeln!(
+                tests,
+                "#[ignore = \"day {day} part {part} has no solver in {SOLUTIONS}\"]"
+            )
+            .unwrap();
+        } else if expected == "?" {
+            writeln!(
+                tests,
+                "#[ignore = \"the answer to {input} isn't known yet\"]"
+            )
+            .unwrap();
+        } else if !key && Path::new(&format!("resources/day_{day:02}/{input}.enc")).exists() {
+            writeln!(
+                tests,
+                "#[ignore = \"{input} is encrypted and there is no key\"]"
+            )
+            .unwrap();
+        }
+        writeln!(
+            tests,
+            "#[test_log::test]\nfn {name}() -> anyhow::Result<()> {{\n    \
+             check({day}, {part}, {input:?}, {expected:?})\n}}\n"
+        )
+        .unwrap();
+    }
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("manifest_tests.rs");
+    fs::write(out, tests).expect("writing the generated tests");
+}
@@ -0,0 +1,425 @@
+//! Client for the puzzle site: downloads the input and the examples of a day
+//! into the input store `cargo aoc` reads from, so each is fetched once. The
+//! tests run it against a local server through [`Options::base_url`].
+
+use std::{
+    env,
+    fs::{self, read_to_string},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use log::{debug, info};
+
+use crate::store::{self, Key};
+
+pub mod submit;
+
+/// Year the store and the URLs are for.
+pub const YEAR: u32 = 2023;
+
+/// Environment variable holding the session token.
+pub const SESSION_VAR: &str = "AOC_SESSION";
+/// File read for the session token when the variable is not set.
+pub const SESSION_FILE: &str = ".aoc-session";
+
+/// The session cookie of a logged in user, from [`SESSION_VAR`] or else
+/// [`SESSION_FILE`].
+pub fn session() -> Result<String> {
+    let session = match env::var(SESSION_VAR) {
+        Ok(session) => session,
+        Err(_) => read_to_string(SESSION_FILE).with_context(|| {
+            format!("Session token is neither in ${SESSION_VAR} nor in {SESSION_FILE}")
+        })?,
+    };
+    let session = session.trim();
+    if session.is_empty() {
+        bail!("Session token is empty");
+    }
+    Ok(session.to_string())
+}
+
+pub struct Options {
+    pub base_url: String,
+    /// Root of the input store; inputs land in `{store}/{YEAR}/day{N}.txt`.
+    pub store: PathBuf,
+    /// Least time between two requests, shared by every client on the store.
+    pub min_interval: Duration,
+    pub user_agent: String,
+    /// Encrypts the inputs stored, see [`crate::store`].
+    pub key: Option<Key>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            base_url: "https://adventofcode.com".to_string(),
+            store: PathBuf::from("input"),
+            min_interval: Duration::from_secs(5),
+            user_agent: format!("aoc23/{}", env!("CARGO_PKG_VERSION")),
+            key: None,
+        }
+    }
+}
+
+pub struct Client {
+    session: String,
+    options: Options,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub fn new(session: String, options: Options) -> Client {
+        let agent = ureq::AgentBuilder::new()
+            .user_agent(&options.user_agent)
+            .timeout(Duration::from_secs(30))
+            .build();
+        Client {
+            session,
+            options,
+            agent,
+        }
+    }
+
+    /// Where the input of `day` is stored.
+    pub fn input_path(&self, day: u32) -> PathBuf {
+        self.options.store.join(format!("{YEAR}/day{day}.txt"))
+    }
+
+    fn page_path(&self, day: u32) -> PathBuf {
+        self.options.store.join(format!("{YEAR}/day{day}.html"))
+    }
+
+    /// The input of `day`, downloaded unless already stored.
+    pub fn input(&self, day: u32) -> Result<String> {
+        let url = format!("/{YEAR}/day/{day}/input");
+        self.cached(&self.input_path(day), &url, self.options.key.as_ref())
+    }
+
+    /// The example blocks of the puzzle text of `day`. The page is stored
+    /// like inputs are; delete it to see the second part once unlocked.
+    pub fn examples(&self, day: u32) -> Result<Vec<String>> {
+        let page = self.cached(&self.page_path(day), &format!("/{YEAR}/day/{day}"), None)?;
+        Ok(examples(&page))
+    }
+
+    // Only inputs are personal, so only they are encrypted with `key`.
+    fn cached(&self, path: &Path, url: &str, key: Option<&Key>) -> Result<String> {
+        if store::exists(path) {
+            debug!("Reading {}", path.display());
+            return store::read_with(path, key);
+        }
+        let body = self.get(url)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Creating {}", dir.display()))?;
+        }
+        store::write(path, &body, key)?;
+        Ok(body)
+    }
+
+    fn post(&self, url: &str, form: &[(&str, &str)]) -> Result<String> {
+        self.throttle()?;
+        info!("Posting to {url}");
+        let response = self
+            .agent
+            .post(&format!("{}{url}", self.options.base_url))
+            .set("Cookie", &format!("session={}", self.session))
+            .send_form(form);
+        read(url, response)
+    }
+
+    fn get(&self, url: &str) -> Result<String> {
+        self.throttle()?;
+        info!("Requesting {url}");
+        let response = self
+            .agent
+            .get(&format!("{}{url}", self.options.base_url))
+            .set("Cookie", &format!("session={}", self.session))
+            .call();
+        read(url, response)
+    }
+
+    // The time of the last request is kept in the store so separate runs of
+    // the runner wait on each other too.
+    fn throttle(&self) -> Result<()> {
+        let stamp = self.options.store.join(".last-request");
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        let last = read_to_string(&stamp)
+            .ok()
+            .and_then(|last| last.trim().parse().ok())
+            .map(Duration::from_millis);
+        if let Some(wait) =
+            last.and_then(|last| (last + self.options.min_interval).checked_sub(now))
+        {
+            debug!("Waiting {wait:?} before the next request");
+            thread::sleep(wait);
+        }
+        fs::create_dir_all(&self.options.store)?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        fs::write(&stamp, now.as_millis().to_string())
+            .with_context(|| format!("Writing {}", stamp.display()))
+    }
+}
+
+// Turns error statuses into errors with the first line the site gave as the
+// reason.
+fn read(url: &str, response: Result<ureq::Response, ureq::Error>) -> Result<String> {
+    match response {
+        Ok(response) => Ok(response.into_string()?),
+        Err(ureq::Error::Status(status, response)) => {
+            let retry = response.header("Retry-After").map(str::to_string);
+            let body = response.into_string().unwrap_or_default();
+            let reason = body.lines().next().unwrap_or_default();
+            match (status, retry) {
+                (404, _) => bail!("{url} is not there (yet?)"),
+                (429, Some(retry)) => bail!("{url}: rate limited, retry in {retry}s"),
+                _ => bail!("{url}: status {status} {reason}"),
+            }
+        }
+        Err(e) => Err(e).with_context(|| format!("Requesting {url}")),
+    }
+}
+
+/// The `<pre><code>` blocks of a puzzle page, as plain text.
+pub fn examples(page: &str) -> Vec<String> {
+    page.split("<pre><code>")
+        .skip(1)
+        .filter_map(|block| block.split_once("</code></pre>"))
+        .map(|(code, _)| unescape(&strip_tags(code)))
+        .collect()
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => (),
+        }
+    }
+    text
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::{
+        error::Error,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use tiny_http::{Header, Request, Response, Server};
+
+    use super::{examples, Client, Options};
+    use crate::store::{self, Key};
+
+    const SESSION: &str = "53cr37";
+
+    const PAGE: &str = "<article><p>For example:</p>\n\
+        <pre><code>1abc2\npqr3stu8vwx\n</code></pre>\n\
+        <p>The <code>&lt;</code> sign, <em>twice</em>:</p>\n\
+        <pre><code><em>a</em> &lt; b &amp;&amp; b &gt; c\n</code></pre></article>";
+
+    /// A stand-in for the puzzle site, answering `handle` to each request on
+    /// its own thread. It counts the requests it gets.
+    pub(super) struct MockSite {
+        pub(super) url: String,
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl MockSite {
+        pub(super) fn start(
+            handle: impl Fn(&mut Request) -> Response<std::io::Cursor<Vec<u8>>> + Send + 'static,
+        ) -> MockSite {
+            let server = Server::http("127.0.0.1:0").unwrap();
+            let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+            let requests = Arc::new(AtomicUsize::new(0));
+            let count = requests.clone();
+            thread::spawn(move || {
+                for mut request in server.incoming_requests() {
+                    count.fetch_add(1, Ordering::SeqCst);
+                    let response = handle(&mut request);
+                    let _ = request.respond(response);
+                }
+            });
+            MockSite { url, requests }
+        }
+
+        pub(super) fn client(&self, store: &std::path::Path) -> Client {
+            Client::new(
+                SESSION.to_string(),
+                Options {
+                    base_url: self.url.clone(),
+                    store: store.to_path_buf(),
+                    min_interval: Duration::ZERO,
+                    ..Default::default()
+                },
+            )
+        }
+
+        pub(super) fn requests(&self) -> usize {
+            self.requests.load(Ordering::SeqCst)
+        }
+    }
+
+    pub(super) fn logged_in(request: &Request) -> bool {
+        request.headers().iter().any(|header| {
+            header.field.equiv("Cookie") && header.value.as_str() == format!("session={SESSION}")
+        })
+    }
+
+    fn site() -> MockSite {
+        MockSite::start(|request| {
+            if !logged_in(request) {
+                return Response::from_string("Puzzle inputs differ by user.")
+                    .with_status_code(400);
+            }
+            match request.url() {
+                "/2023/day/1/input" => Response::from_string("1abc2\n"),
+                "/2023/day/1" => Response::from_string(PAGE)
+                    .with_header("Content-Type: text/html".parse::<Header>().unwrap()),
+                _ => Response::from_string("404 Not Found").with_status_code(404),
+            }
+        })
+    }
+
+    #[test_log::test]
+    fn input_is_stored() -> Result<(), Box<dyn Error>> {
+        let site = site();
+        let store = tempfile::tempdir()?;
+        let client = site.client(store.path());
+        assert_eq!(client.input(1)?, "1abc2\n");
+        assert_eq!(client.input(1)?, "1abc2\n");
+        assert_eq!(site.requests(), 1);
+        assert_eq!(
+            std::fs::read_to_string(store.path().join("2023/day1.txt"))?,
+            "1abc2\n"
+        );
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn input_is_stored_encrypted() -> Result<(), Box<dyn Error>> {
+        let site = site();
+        let store = tempfile::tempdir()?;
+        let key = Key::generate();
+        let client = Client::new(
+            SESSION.to_string(),
+            Options {
+                base_url: site.url.clone(),
+                store: store.path().to_path_buf(),
+                min_interval: Duration::ZERO,
+                key: Some(key.clone()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(client.input(1)?, "1abc2\n");
+        assert_eq!(client.input(1)?, "1abc2\n");
+        assert_eq!(site.requests(), 1);
+        let path = client.input_path(1);
+        assert!(!path.exists());
+        assert_eq!(store::read_with(&path, Some(&key))?, "1abc2\n");
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn examples_of_the_page() -> Result<(), Box<dyn Error>> {
+        let site = site();
+        let store = tempfile::tempdir()?;
+        let client = site.client(store.path());
+        let expected = vec!["1abc2\npqr3stu8vwx\n", "a < b && b > c\n"];
+        assert_eq!(client.examples(1)?, expected);
+        assert_eq!(client.examples(1)?, expected);
+        assert_eq!(site.requests(), 1);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn no_examples() {
+        assert!(examples("<p>Nothing <code>here</code></p>").is_empty());
+    }
+
+    #[test_log::test]
+    fn wrong_session() -> Result<(), Box<dyn Error>> {
+        let site = site();
+        let store = tempfile::tempdir()?;
+        let client = Client::new(
+            "wrong".to_string(),
+            Options {
+                base_url: site.url.clone(),
+                store: store.path().to_path_buf(),
+                min_interval: Duration::ZERO,
+                ..Default::default()
+            },
+        );
+        let error = client.input(1).unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "/2023/day/1/input: status 400 Puzzle inputs differ by user."
+        );
+        assert!(!store.path().join("2023/day1.txt").exists());
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn locked_day() -> Result<(), Box<dyn Error>> {
+        let site = site();
+        let store = tempfile::tempdir()?;
+        let error = site.client(store.path()).input(25).unwrap_err();
+        assert_eq!(error.to_string(), "/2023/day/25/input is not there (yet?)");
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn rate_limited() -> Result<(), Box<dyn Error>> {
+        let site = site();
+        let store = tempfile::tempdir()?;
+        let options = || Options {
+            base_url: site.url.clone(),
+            store: store.path().to_path_buf(),
+            min_interval: Duration::from_millis(300),
+            ..Default::default()
+        };
+        // Two clients, as two runs of the runner would be.
+        let start = Instant::now();
+        Client::new(SESSION.to_string(), options()).input(1)?;
+        Client::new(SESSION.to_string(), options()).examples(1)?;
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert_eq!(site.requests(), 2);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn too_many_requests() -> Result<(), Box<dyn Error>> {
+        let site = MockSite::start(|_| {
+            Response::from_string("Slow down")
+                .with_status_code(429)
+                .with_header("Retry-After: 60".parse::<Header>().unwrap())
+        });
+        let store = tempfile::tempdir()?;
+        let error = site.client(store.path()).input(1).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "/2023/day/1/input: rate limited, retry in 60s"
+        );
+        Ok(())
+    }
+}
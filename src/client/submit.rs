@@ -0,0 +1,416 @@
+//! Answer submission. Every verdict of the site is kept in a history next to
+//! the inputs, which answers in its place whenever it already can: for an
+//! answer sent before, or one beyond an answer known to be too high or low.
+
+use std::{
+    fmt,
+    fs::{self, read_to_string, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+
+use super::{strip_tags, Client, YEAR};
+
+/// An answer as typed in the site's form: a single word, most often a number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answer(String);
+
+impl Answer {
+    pub fn number(&self) -> Option<i128> {
+        self.0.parse().ok()
+    }
+}
+
+impl FromStr for Answer {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let answer = s.trim();
+        if answer.is_empty() || answer.contains(char::is_whitespace) {
+            bail!("An answer is a single word, not {s:?}");
+        }
+        Ok(Answer(answer.to_string()))
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+    TooHigh,
+    TooLow,
+}
+
+/// What the site made of an answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Right,
+    Wrong(Option<Hint>),
+    /// Answered too soon after a wrong answer; nothing was checked.
+    Wait(Duration),
+    /// The part is already solved, or not unlocked yet.
+    WrongLevel,
+}
+
+impl Verdict {
+    /// Reads the verdict off the page the site answers a submission with.
+    pub fn parse(page: &str) -> Result<Verdict> {
+        let text = match page.split_once("<article>") {
+            Some((_, article)) => article.split("</article>").next().unwrap_or_default(),
+            None => page,
+        };
+        let text = strip_tags(text);
+        if text.contains("That's the right answer") {
+            return Ok(Verdict::Right);
+        }
+        if text.contains("That's not the right answer") {
+            let hint = if text.contains("too high") {
+                Some(Hint::TooHigh)
+            } else if text.contains("too low") {
+                Some(Hint::TooLow)
+            } else {
+                None
+            };
+            return Ok(Verdict::Wrong(hint));
+        }
+        if text.contains("You gave an answer too recently") {
+            let wait = Regex::new(r"You have (?:(\d+)m )?(\d+)s left to wait")?;
+            let Some(captures) = wait.captures(&text) else {
+                bail!("No time to wait in {text:?}");
+            };
+            let minutes: u64 = captures.get(1).map_or(Ok(0), |m| m.as_str().parse())?;
+            let seconds: u64 = captures[2].parse()?;
+            return Ok(Verdict::Wait(Duration::from_secs(minutes * 60 + seconds)));
+        }
+        if text.contains("You don't seem to be solving the right level") {
+            return Ok(Verdict::WrongLevel);
+        }
+        bail!("Unexpected answer page: {:?}", text.trim())
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Right => write!(f, "right"),
+            Verdict::Wrong(None) => write!(f, "wrong"),
+            Verdict::Wrong(Some(Hint::TooHigh)) => write!(f, "too high"),
+            Verdict::Wrong(Some(Hint::TooLow)) => write!(f, "too low"),
+            Verdict::Wait(wait) => write!(f, "wait {}s", wait.as_secs()),
+            Verdict::WrongLevel => write!(f, "wrong level"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Submission {
+    /// The site was asked.
+    Sent(Verdict),
+    /// The history already knew, so nothing was sent.
+    Known(Verdict),
+}
+
+struct Entry {
+    day: u32,
+    part: u32,
+    answer: Answer,
+    verdict: Verdict,
+}
+
+// One line per entry: `day part verdict answer`, verdicts being `right`,
+// `wrong`, `too-high` or `too-low`.
+impl FromStr for Entry {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let fields: Vec<_> = s.split_whitespace().collect();
+        let [day, part, verdict, answer] = fields[..] else {
+            bail!("Expected `day part verdict answer`, got {s:?}");
+        };
+        let verdict = match verdict {
+            "right" => Verdict::Right,
+            "wrong" => Verdict::Wrong(None),
+            "too-high" => Verdict::Wrong(Some(Hint::TooHigh)),
+            "too-low" => Verdict::Wrong(Some(Hint::TooLow)),
+            _ => bail!("Unknown verdict {verdict}"),
+        };
+        Ok(Entry {
+            day: day.parse()?,
+            part: part.parse()?,
+            answer: answer.parse()?,
+            verdict,
+        })
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verdict = match self.verdict {
+            Verdict::Right => "right",
+            Verdict::Wrong(None) => "wrong",
+            Verdict::Wrong(Some(Hint::TooHigh)) => "too-high",
+            Verdict::Wrong(Some(Hint::TooLow)) => "too-low",
+            Verdict::Wait(_) | Verdict::WrongLevel => unreachable!("never recorded"),
+        };
+        write!(f, "{} {} {verdict} {}", self.day, self.part, self.answer)
+    }
+}
+
+/// The verdicts given so far, as stored in the input store.
+pub struct History {
+    path: PathBuf,
+    entries: Vec<Entry>,
+}
+
+impl History {
+    pub fn load(path: PathBuf) -> Result<History> {
+        let entries = if path.exists() {
+            read_to_string(&path)
+                .with_context(|| format!("Reading {}", path.display()))?
+                .lines()
+                .enumerate()
+                .map(|(i, line)| {
+                    line.parse()
+                        .with_context(|| format!("{} line {}", path.display(), i + 1))
+                })
+                .collect::<Result<_>>()?
+        } else {
+            Vec::new()
+        };
+        Ok(History { path, entries })
+    }
+
+    /// The verdict the site would give, when it follows from earlier ones.
+    pub fn known(&self, day: u32, part: u32, answer: &Answer) -> Option<Verdict> {
+        let entries = || {
+            self.entries
+                .iter()
+                .filter(move |entry| entry.day == day && entry.part == part)
+        };
+        if let Some(entry) = entries().find(|entry| entry.answer == *answer) {
+            return Some(entry.verdict);
+        }
+        if entries().any(|entry| entry.verdict == Verdict::Right) {
+            return Some(Verdict::Wrong(None));
+        }
+        let number = answer.number()?;
+        let bound = |hint| {
+            entries()
+                .filter(move |entry| entry.verdict == Verdict::Wrong(Some(hint)))
+                .filter_map(|entry| entry.answer.number())
+        };
+        if bound(Hint::TooHigh).any(|high| number >= high) {
+            return Some(Verdict::Wrong(Some(Hint::TooHigh)));
+        }
+        if bound(Hint::TooLow).any(|low| number <= low) {
+            return Some(Verdict::Wrong(Some(Hint::TooLow)));
+        }
+        None
+    }
+
+    /// Keeps `verdict`, unless it says nothing about the answer.
+    pub fn record(&mut self, day: u32, part: u32, answer: &Answer, verdict: Verdict) -> Result<()> {
+        if matches!(verdict, Verdict::Wait(_) | Verdict::WrongLevel) {
+            return Ok(());
+        }
+        let entry = Entry {
+            day,
+            part,
+            answer: answer.clone(),
+            verdict,
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Opening {}", self.path.display()))?;
+        writeln!(file, "{entry}")?;
+        self.entries.push(entry);
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Where the verdicts on the answers are kept.
+    pub fn history_path(&self) -> PathBuf {
+        self.options.store.join(format!("{YEAR}/submissions.txt"))
+    }
+
+    /// Sends `answer` for `part` of `day`, unless the history already knows
+    /// the verdict.
+    pub fn submit(&self, day: u32, part: u32, answer: &Answer) -> Result<Submission> {
+        let mut history = History::load(self.history_path())?;
+        if let Some(verdict) = history.known(day, part, answer) {
+            return Ok(Submission::Known(verdict));
+        }
+        let page = self.post(
+            &format!("/{YEAR}/day/{day}/answer"),
+            &[("level", &part.to_string()), ("answer", &answer.0)],
+        )?;
+        let verdict = Verdict::parse(&page)?;
+        history.record(day, part, answer, verdict)?;
+        Ok(Submission::Sent(verdict))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::{
+        error::Error,
+        fs::read_to_string,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use tiny_http::Response;
+
+    use super::{Answer, Hint, History, Submission, Verdict};
+    use crate::client::test::{logged_in, MockSite};
+
+    fn page(text: &str) -> String {
+        format!("<html><main><article><p>{text}</p></article></main></html>")
+    }
+
+    // Answers 42 to part 1 of day 1, and keeps the forms posted to it.
+    fn site() -> (MockSite, Arc<Mutex<Vec<String>>>) {
+        let forms = Arc::new(Mutex::new(Vec::new()));
+        let posted = forms.clone();
+        let site = MockSite::start(move |request| {
+            if !logged_in(request) || request.url() != "/2023/day/1/answer" {
+                return Response::from_string("").with_status_code(400);
+            }
+            let mut form = String::new();
+            request.as_reader().read_to_string(&mut form).unwrap();
+            let text = match form.as_str() {
+                "level=1&answer=42" => "That's the right answer! You are one gold star closer.",
+                "level=1&answer=100" => {
+                    "That's not the right answer; your answer is too high. Please wait one minute."
+                }
+                "level=1&answer=1" => "That's not the right answer; your answer is too low.",
+                "level=1&answer=soon" => {
+                    "You gave an answer too recently; you have to wait after submitting an \
+                     answer before trying again.  You have 1m 5s left to wait."
+                }
+                _ => "That's not the right answer.",
+            };
+            posted.lock().unwrap().push(form);
+            Response::from_string(page(text))
+        });
+        (site, forms)
+    }
+
+    #[test_log::test]
+    fn verdicts() -> Result<(), Box<dyn Error>> {
+        let (site, forms) = site();
+        let store = tempfile::tempdir()?;
+        let client = site.client(store.path());
+        let submit = |answer: &str| client.submit(1, 1, &answer.parse()?);
+
+        assert_eq!(
+            submit("100")?,
+            Submission::Sent(Verdict::Wrong(Some(Hint::TooHigh)))
+        );
+        assert_eq!(
+            submit("1")?,
+            Submission::Sent(Verdict::Wrong(Some(Hint::TooLow)))
+        );
+        assert_eq!(submit("7")?, Submission::Sent(Verdict::Wrong(None)));
+        assert_eq!(
+            submit("soon")?,
+            Submission::Sent(Verdict::Wait(Duration::from_secs(65)))
+        );
+        assert_eq!(submit("42")?, Submission::Sent(Verdict::Right));
+        assert_eq!(forms.lock().unwrap().len(), 5);
+
+        assert_eq!(
+            read_to_string(client.history_path())?,
+            "1 1 too-high 100\n1 1 too-low 1\n1 1 wrong 7\n1 1 right 42\n"
+        );
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn known_answers_are_not_sent() -> Result<(), Box<dyn Error>> {
+        let (site, forms) = site();
+        let store = tempfile::tempdir()?;
+        let client = site.client(store.path());
+        let submit = |answer: &str| client.submit(1, 1, &answer.parse()?);
+
+        submit("100")?;
+        submit("7")?;
+        assert_eq!(
+            submit("100")?,
+            Submission::Known(Verdict::Wrong(Some(Hint::TooHigh)))
+        );
+        assert_eq!(
+            submit("250")?,
+            Submission::Known(Verdict::Wrong(Some(Hint::TooHigh)))
+        );
+        assert_eq!(submit("7")?, Submission::Known(Verdict::Wrong(None)));
+        // Waiting is no verdict on the answer, so it is sent again.
+        submit("soon")?;
+        submit("soon")?;
+        assert_eq!(forms.lock().unwrap().len(), 4);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn bounds() -> Result<(), Box<dyn Error>> {
+        let store = tempfile::tempdir()?;
+        let path = store.path().join("submissions.txt");
+        let mut history = History::load(path.clone())?;
+        let answer = |answer: &str| answer.parse::<Answer>().unwrap();
+        history.record(3, 2, &answer("100"), Verdict::Wrong(Some(Hint::TooHigh)))?;
+        history.record(3, 2, &answer("10"), Verdict::Wrong(Some(Hint::TooLow)))?;
+        history.record(3, 1, &answer("5"), Verdict::Right)?;
+
+        // Read back from the file, as the next run would.
+        let history = History::load(path)?;
+        let known = |part, answer: &str| history.known(3, part, &answer.parse().unwrap());
+        assert_eq!(known(2, "101"), Some(Verdict::Wrong(Some(Hint::TooHigh))));
+        assert_eq!(known(2, "10"), Some(Verdict::Wrong(Some(Hint::TooLow))));
+        assert_eq!(known(2, "-3"), Some(Verdict::Wrong(Some(Hint::TooLow))));
+        assert_eq!(known(2, "50"), None);
+        assert_eq!(known(2, "word"), None);
+        assert_eq!(known(1, "5"), Some(Verdict::Right));
+        assert_eq!(known(1, "6"), Some(Verdict::Wrong(None)));
+        assert_eq!(history.known(4, 2, &answer("50")), None);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn wrong_level() -> Result<(), Box<dyn Error>> {
+        let verdict = Verdict::parse(&page(
+            "You don't seem to be solving the right level.  Did you already complete it?",
+        ))?;
+        assert_eq!(verdict, Verdict::WrongLevel);
+        assert_eq!(
+            Verdict::parse(&page("You have 30s left to wait."))
+                .unwrap_err()
+                .to_string(),
+            "Unexpected answer page: \"You have 30s left to wait.\""
+        );
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn answers() {
+        assert_eq!("  42\n".parse::<Answer>().unwrap().number(), Some(42));
+        assert_eq!("abc".parse::<Answer>().unwrap().number(), None);
+        assert!("".parse::<Answer>().is_err());
+        assert!("4 2".parse::<Answer>().is_err());
+    }
+}
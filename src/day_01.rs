@@ -1,32 +1,91 @@
-use anyhow::Context;
+//! Day 1: Trebuchet?! The model is a [`Document`] of calibration lines.
 
-#[aoc(day1, part1)]
-pub fn problem_1(input: &str) -> usize {
-    let calibration_lines = input
-        .lines()
-        .map(|line| {
-            line.chars()
-                .fold(None, |value, c| match value {
-                    Some((first, last)) => Some((first, if c.is_ascii_digit() { c } else { last })),
-                    None => {
-                        if c.is_ascii_digit() {
-                            Some((c, c))
-                        } else {
-                            None
-                        }
-                    }
-                })
-                .context("All lines should contain a digit.")
-                .unwrap()
+use std::{convert::Infallible, fmt, io::BufRead, str::FromStr};
+
+use anyhow::{Context, Result};
+use log::{debug, trace};
+
+use crate::streaming::try_fold_lines;
+
+/// The amended calibration document, one value hidden in every line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Document {
+    lines: Vec<String>,
+}
+
+impl FromStr for Document {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Document {
+            lines: s.lines().map(str::to_string).collect(),
         })
-        .map(|(first, last)| format!("{}{}", first, last))
-        .map(|value| value.parse::<usize>().unwrap());
+    }
+}
 
-    calibration_lines.sum()
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lines.join("\n"))
+    }
 }
 
-#[aoc(day1, part2)]
-pub fn problem_2(input: &str) -> usize {
+impl Document {
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().map(String::as_str)
+    }
+
+    /// Calibration value of every line, reading digits only.
+    pub fn values(&self) -> Result<Vec<usize>> {
+        self.lines().map(calibration_value).collect()
+    }
+
+    /// Calibration value of every line, reading spelled out digits too.
+    pub fn spelled_values(&self) -> Result<Vec<usize>> {
+        self.lines().map(spelled_calibration_value).collect()
+    }
+
+    pub fn solve_1(&self) -> Result<usize> {
+        Ok(self.values()?.into_iter().sum())
+    }
+
+    pub fn solve_2(&self) -> Result<usize> {
+        Ok(self.spelled_values()?.into_iter().sum())
+    }
+}
+
+fn calibration_value(line: &str) -> Result<usize> {
+    let (first, last) = line
+        .chars()
+        .fold(None, |value, c| match value {
+            Some((first, last)) => Some((first, if c.is_ascii_digit() { c } else { last })),
+            None => {
+                if c.is_ascii_digit() {
+                    Some((c, c))
+                } else {
+                    None
+                }
+            }
+        })
+        .context("All lines should contain a digit.")?;
+
+    let value = format!("{}{}", first, last).parse::<usize>()?;
+    trace!("Calibration value: {value}");
+    Ok(value)
+}
+
+#[aoc(day1, part1)]
+pub fn problem_1(input: &str) -> Result<usize> {
+    problem_1_reader(input.as_bytes())
+}
+
+pub fn problem_1_reader<R: BufRead>(reader: R) -> Result<usize> {
+    let sum = try_fold_lines(reader, 0, |sum, line| Ok(sum + calibration_value(line)?))?;
+    debug!("Sum of calibration values: {sum}");
+    Ok(sum)
+}
+
+fn spelled_calibration_value(mut line: &str) -> Result<usize> {
     use nom::{
         branch::alt,
         bytes::complete::{tag, take},
@@ -52,50 +111,58 @@ pub fn problem_2(input: &str) -> usize {
         take(1usize)(s)
     }
 
-    let calibration_lines = input
-        .lines()
-        .map(|mut line| {
-            let mut calibration = None;
-            loop {
-                let value = if let Ok((_, value)) = spelled(line) {
-                    Some(match value {
-                        "one" => "1",
-                        "two" => "2",
-                        "three" => "3",
-                        "four" => "4",
-                        "five" => "5",
-                        "six" => "6",
-                        "seven" => "7",
-                        "eight" => "8",
-                        "nine" => "9",
-                        x => unreachable!("Guaranteed by the spelled function: {}", x),
-                    })
-                } else if let Ok((_, value)) = take1(line) {
-                    if value.chars().next().unwrap().is_ascii_digit() {
-                        Some(value)
-                    } else {
-                        None
-                    }
-                } else {
-                    break;
-                };
+    let mut calibration = None;
+    loop {
+        let value = if let Ok((_, value)) = spelled(line) {
+            Some(match value {
+                "one" => "1",
+                "two" => "2",
+                "three" => "3",
+                "four" => "4",
+                "five" => "5",
+                "six" => "6",
+                "seven" => "7",
+                "eight" => "8",
+                "nine" => "9",
+                x => unreachable!("Guaranteed by the spelled function: {}", x),
+            })
+        } else if let Ok((_, value)) = take1(line) {
+            if value.chars().next().unwrap().is_ascii_digit() {
+                Some(value)
+            } else {
+                None
+            }
+        } else {
+            break;
+        };
 
-                //line = value.0;
-                (line, _) = take1(line).unwrap();
+        (line, _) = take1(line).unwrap();
 
-                if let Some(value) = value {
-                    calibration = match calibration {
-                        Some((first, _)) => Some((first, value)),
-                        None => Some((value, value)),
-                    }
-                }
+        if let Some(value) = value {
+            calibration = match calibration {
+                Some((first, _)) => Some((first, value)),
+                None => Some((value, value)),
             }
-            calibration.unwrap()
-        })
-        .map(|(first, last)| format!("{}{}", first, last))
-        .map(|value| value.parse::<usize>().unwrap());
+        }
+    }
+    let (first, last) = calibration.context("All lines should contain a digit.")?;
 
-    calibration_lines.sum()
+    let value = format!("{}{}", first, last).parse::<usize>()?;
+    trace!("Calibration value: {value}");
+    Ok(value)
+}
+
+#[aoc(day1, part2)]
+pub fn problem_2(input: &str) -> Result<usize> {
+    problem_2_reader(input.as_bytes())
+}
+
+pub fn problem_2_reader<R: BufRead>(reader: R) -> Result<usize> {
+    let sum = try_fold_lines(reader, 0, |sum, line| {
+        Ok(sum + spelled_calibration_value(line)?)
+    })?;
+    debug!("Sum of calibration values: {sum}");
+    Ok(sum)
 }
 
 #[cfg(test)]
@@ -103,40 +170,40 @@ mod test {
     use std::error::Error;
     use std::fs::read_to_string;
 
-    use super::problem_1;
-    use super::problem_2;
-
-    #[test]
-    fn train_problem_1() -> Result<(), Box<dyn Error>> {
-        let calibration_lines = read_to_string("resources/day_01/problem_1_train.inp")?;
-        let result = problem_1(&calibration_lines);
-        assert_eq!(result, 142);
-        Ok(())
-    }
-
     #[test_log::test]
-    fn solve_problem_1() -> Result<(), Box<dyn Error>> {
-        let calibration_lines = read_to_string("resources/day_01/problem_1.inp")?;
-        let result = problem_1(&calibration_lines);
+    fn streaming() -> Result<(), Box<dyn Error>> {
+        use super::{problem_1_reader, problem_2_reader};
+        use std::{fs::File, io::BufReader};
+
+        let input = BufReader::new(File::open("resources/day_01/train_problem_2.inp")?);
+        assert_eq!(problem_2_reader(input)?, 281);
 
-        println!("{result}");
+        let error = problem_1_reader("1abc2\nnodigits\n".as_bytes()).unwrap_err();
+        assert_eq!(error.to_string(), "Line 2");
         Ok(())
     }
 
-    #[test]
-    fn train_problem_2() -> Result<(), Box<dyn Error>> {
-        let calibration_lines = read_to_string("resources/day_01/problem_2_train.inp")?;
-        let result = problem_2(&calibration_lines);
-        assert_eq!(result, 281);
+    #[test_log::test]
+    fn document() -> Result<(), Box<dyn Error>> {
+        use super::Document;
+
+        let document: Document = read_to_string("resources/day_01/train_problem_2.inp")?.parse()?;
+        assert_eq!(document.lines().next(), Some("two1nine"));
+        assert_eq!(document.spelled_values()?[..3], [29, 83, 13]);
+        assert_eq!(document.solve_2()?, 281);
+        assert!(document.solve_1().is_err());
         Ok(())
     }
 
-    #[test]
-    fn solve_problem_2() -> Result<(), Box<dyn Error>> {
-        let calibration_lines = read_to_string("resources/day_01/problem_2.inp")?;
-        let result = problem_2(&calibration_lines);
-
-        println!("{result}");
+    #[test_log::test]
+    fn display_round_trip() -> Result<(), Box<dyn Error>> {
+        use super::Document;
+
+        for (path, input) in crate::resources::inputs(1)? {
+            let document: Document = input.parse()?;
+            assert_eq!(document.to_string(), input.trim_end(), "{path:?}");
+            assert_eq!(document.to_string().parse::<Document>()?, document);
+        }
         Ok(())
     }
 }
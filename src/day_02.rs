@@ -1,145 +1,252 @@
-use anyhow::{bail, Context, Result};
+//! Day 2: Cube Conundrum. The model is a [`Record`] of [`Game`]s.
+
+use std::{fmt, str::FromStr};
+
+use anyhow::{Context, Result};
+use log::trace;
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_till},
-    character::complete::{digit1, space1},
-    multi::many0,
-    sequence::{terminated, tuple},
+    bytes::complete::tag,
+    character::complete::space1,
+    multi::separated_list1,
+    sequence::{pair, separated_pair},
 };
 
-#[derive(Default, Debug, Clone)]
-struct Round {
+use crate::parsers::{header, lines, number, parse, IResult};
+
+/// Cubes of each color, as shown in a round or held in the bag.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Round {
     red: usize,
     green: usize,
     blue: usize,
 }
 
-#[derive(Debug)]
-struct Game {
+impl Round {
+    pub fn new(red: usize, green: usize, blue: usize) -> Round {
+        Round { red, green, blue }
+    }
+
+    pub fn red(&self) -> usize {
+        self.red
+    }
+
+    pub fn green(&self) -> usize {
+        self.green
+    }
+
+    pub fn blue(&self) -> usize {
+        self.blue
+    }
+
+    /// Whether every cube shown could have come out of `bag`.
+    pub fn fits(&self, bag: &Round) -> bool {
+        self.red <= bag.red && self.green <= bag.green && self.blue <= bag.blue
+    }
+
+    pub fn power(&self) -> usize {
+        self.red * self.green * self.blue
+    }
+}
+
+/// Writes the colors shown, always in red, green, blue order.
+impl fmt::Display for Round {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let colors = [
+            (self.red, "red"),
+            (self.green, "green"),
+            (self.blue, "blue"),
+        ];
+        let shown: Vec<_> = colors
+            .iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, color)| format!("{count} {color}"))
+            .collect();
+        write!(f, "{}", shown.join(", "))
+    }
+}
+
+/// The bag the elf asks about in part 1.
+pub const BAG: Round = Round {
+    red: 12,
+    green: 13,
+    blue: 14,
+};
+
+/// One line of the record: every round shown in a game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Game {
     id: usize,
     rounds: Vec<Round>,
 }
 
-fn parse_game(game: &str) -> Result<Game> {
-    let parse_game = tag::<_, _, nom::error::Error<_>>("Game ");
-    // This forces game to outlive static because of the ? after the Result.
-    // I would be nice to find a way to use ? without requiring game to outlive
-    // parse_game.
-    //
-    // let (game, _) = parse_game(game).with_context(|| format!(r#"line should start with "Game""#))?;
-    //
-    // for that reason form now on we unwrap:
-    let (game, _) = parse_game(game).unwrap();
-
-    let mut parse_id = terminated(
-        take_till::<_, _, nom::error::Error<_>>(|c| c == ':'),
-        tag(":"),
-    );
-    let (game, id) = parse_id(game).unwrap();
-    let id: usize = id.parse().context("id is not a number")?;
-
-    let mut rounds = vec![];
-    let mut parse_round = tuple((
-        space1::<_, nom::error::Error<_>>,
-        digit1,
-        space1,
-        alt((tag("red"), tag("green"), tag("blue"))),
-        many0(alt((tag(","), tag(";")))),
-    ));
+impl FromStr for Game {
+    type Err = anyhow::Error;
 
-    let mut round = Round::default();
-    let mut game = game;
+    fn from_str(s: &str) -> Result<Self> {
+        parse(s, game)
+    }
+}
 
-    loop {
-        let (game_rest, (_, count, _, color, terminator)) = parse_round(game).unwrap();
-        let count: usize = count.parse().context("count is not a number")?;
-        match color {
-            "red" => round.red = count,
-            "green" => round.green = count,
-            "blue" => round.blue = count,
-            _ => bail!("Unknown color: {color}"),
-        }
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rounds: Vec<_> = self.rounds.iter().map(Round::to_string).collect();
+        write!(f, "Game {}: {}", self.id, rounds.join("; "))
+    }
+}
 
-        match terminator.first() {
-            Some(&",") => (),
-            Some(&";") => {
-                rounds.push(round);
-                round = Round::default();
-            }
-            None => {
-                rounds.push(round);
-                break;
-            }
-            _ => unreachable!("guaranteed by parse_round"),
-        }
+impl Game {
+    pub fn id(&self) -> usize {
+        self.id
+    }
 
-        if game_rest.is_empty() {
-            break;
-        }
+    pub fn rounds(&self) -> &[Round] {
+        &self.rounds
+    }
+
+    pub fn is_possible(&self, bag: &Round) -> bool {
+        self.rounds.iter().all(|round| round.fits(bag))
+    }
 
-        game = game_rest;
+    /// The smallest bag the game could be played with, if it has any rounds.
+    pub fn minimum(&self) -> Option<Round> {
+        self.rounds.iter().copied().reduce(|min, round| Round {
+            red: std::cmp::max(min.red, round.red),
+            green: std::cmp::max(min.green, round.green),
+            blue: std::cmp::max(min.blue, round.blue),
+        })
     }
+}
+
+/// Every game played, in the order they were recorded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Record {
+    games: Vec<Game>,
+}
+
+impl FromStr for Record {
+    type Err = anyhow::Error;
 
-    Ok(Game { id, rounds })
+    fn from_str(s: &str) -> Result<Self> {
+        let games = parse(s, lines(game))?;
+        Ok(Record { games })
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let games: Vec<_> = self.games.iter().map(Game::to_string).collect();
+        write!(f, "{}", games.join("\n"))
+    }
+}
+
+impl Record {
+    pub fn games(&self) -> &[Game] {
+        &self.games
+    }
+
+    pub fn solve_1(&self) -> usize {
+        self.games
+            .iter()
+            .filter(|game| game.is_possible(&BAG))
+            .map(Game::id)
+            .sum()
+    }
+
+    pub fn solve_2(&self) -> Result<usize> {
+        self.games
+            .iter()
+            .map(|game| {
+                let min = game.minimum().context("game has no rounds")?;
+                Ok(min.power())
+            })
+            .sum()
+    }
+}
+
+// `Game N: ` and its rounds, separated by `; `.
+fn game(input: &str) -> IResult<'_, Game> {
+    let (input, (id, rounds)) = pair(header("Game"), separated_list1(tag("; "), round))(input)?;
+    trace!("Parsed game {id} with {} rounds", rounds.len());
+    Ok((input, Game { id, rounds }))
+}
+
+// Counts of colors, such as `3 blue, 4 red`.
+fn round(input: &str) -> IResult<'_, Round> {
+    let draw = separated_pair(number, space1, alt((tag("red"), tag("green"), tag("blue"))));
+    let (input, draws) = separated_list1(tag(", "), draw)(input)?;
+    let mut round = Round::default();
+    for (count, color) in draws {
+        match color {
+            "red" => round.red = count,
+            "green" => round.green = count,
+            _ => round.blue = count,
+        }
+    }
+    Ok((input, round))
 }
 
 pub mod problem_1 {
 
-    use super::{parse_game, Round};
+    use std::io::BufRead;
+
+    use super::{Game, BAG};
+    use crate::streaming::try_fold_lines;
     use anyhow::{Context, Result};
+    use log::{debug, trace};
+
+    // The game id if it could be played with the bag's cubes.
+    fn possible_id(line: &str) -> Result<Option<usize>> {
+        let game: Game = line.parse().context("could not parse the game")?;
+        if !game.is_possible(&BAG) {
+            trace!("Game {} is impossible", game.id);
+            return Ok(None);
+        }
 
-    const COUNT_RED: usize = 12;
-    const COUNT_GREEN: usize = 13;
-    const COUNT_BLUE: usize = 14;
+        Ok(Some(game.id))
+    }
 
     pub fn solve(input: &str) -> Result<usize> {
-        Ok(input
-            .lines()
-            .map(|line| {
-                parse_game(line)
-                    .context("could not parse the game")
-                    .unwrap()
-            })
-            .filter_map(|game| {
-                let rounds: Vec<Round> = game.rounds;
-                if rounds.iter().any(|&Round { red, green, blue }| {
-                    red > COUNT_RED || green > COUNT_GREEN || blue > COUNT_BLUE
-                }) {
-                    return None;
-                }
-
-                Some(game.id)
-            })
-            .sum())
+        solve_reader(input.as_bytes())
+    }
+
+    pub fn solve_reader<R: BufRead>(reader: R) -> Result<usize> {
+        let sum = try_fold_lines(reader, 0, |sum, line| {
+            Ok(sum + possible_id(line)?.unwrap_or(0))
+        })?;
+        debug!("Sum of possible game ids: {sum}");
+        Ok(sum)
     }
 }
 
 pub mod problem_2 {
 
-    use super::{parse_game, Round};
+    use std::io::BufRead;
+
+    use super::Game;
+    use crate::streaming::try_fold_lines;
     use anyhow::{Context, Result};
+    use log::{debug, trace};
+
+    // Power of the smallest set of cubes the game could be played with.
+    fn power(line: &str) -> Result<usize> {
+        let game: Game = line.parse().context("could not parse the game")?;
+        let min = game.minimum().context("game has no rounds")?;
+        trace!("Game {} needs at least {min:?}", game.id);
+        Ok(min.power())
+    }
 
     pub fn solve(input: &str) -> Result<usize> {
-        Ok(input
-            .lines()
-            .map(|line| {
-                parse_game(line)
-                    .context("could not parse the game")
-                    .unwrap()
-            })
-            .map(|game| {
-                let min = game
-                    .rounds
-                    .into_iter()
-                    .reduce(|min, round| Round {
-                        red: std::cmp::max(min.red, round.red),
-                        green: std::cmp::max(min.green, round.green),
-                        blue: std::cmp::max(min.blue, round.blue),
-                    })
-                    .unwrap();
-                min.red * min.green * min.blue
-            })
-            .sum())
+        solve_reader(input.as_bytes())
+    }
+
+    pub fn solve_reader<R: BufRead>(reader: R) -> Result<usize> {
+        let sum = try_fold_lines(reader, 0, |sum, line| Ok(sum + power(line)?))?;
+        debug!("Sum of minimum set powers: {sum}");
+        Ok(sum)
     }
 }
 
@@ -149,47 +256,50 @@ mod test {
     use std::error::Error;
     use std::fs::read_to_string;
 
-    #[test]
-    fn train_problem_1() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
-        let input = read_to_string("resources/day_02/train_problem_1.inp")?;
-        let result = solve(&input)?;
-
-        assert_eq!(result, 8);
-
-        Ok(())
-    }
-
-    #[test]
-    fn solve_problem_1() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
-        let input = read_to_string("resources/day_02/problem_1.inp")?;
-        let result = solve(&input)?;
+    #[test_log::test]
+    fn streaming() -> Result<(), Box<dyn Error>> {
+        use super::{problem_1, problem_2};
+        use std::{fs::File, io::BufReader};
 
-        println!("{result}");
+        let input = BufReader::new(File::open("resources/day_02/train_problem_2.inp")?);
+        assert_eq!(problem_2::solve_reader(input)?, 2286);
 
+        let input = "Game 1: 3 blue\nGame 2: 3 purple\n";
+        let error = problem_1::solve_reader(input.as_bytes()).unwrap_err();
+        assert_eq!(error.to_string(), "Line 2");
         Ok(())
     }
 
-    #[test]
-    fn train_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_2::solve;
-        let input = read_to_string("resources/day_02/train_problem_2.inp")?;
-        let result = solve(&input)?;
-
-        assert_eq!(result, 2286);
-
+    #[test_log::test]
+    fn record() -> Result<(), Box<dyn Error>> {
+        use super::{Record, Round, BAG};
+
+        let record: Record = read_to_string("resources/day_02/train_problem_1.inp")?.parse()?;
+        let game = &record.games()[2];
+        assert_eq!(game.id(), 3);
+        assert_eq!(game.rounds()[0], Round::new(20, 8, 6));
+        assert!(!game.is_possible(&BAG));
+        assert_eq!(game.minimum(), Some(Round::new(20, 13, 6)));
+        assert_eq!(record.solve_1(), 8);
+        assert_eq!(record.solve_2()?, 2286);
         Ok(())
     }
 
-    #[test]
-    fn solve_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_2::solve;
-        let input = read_to_string("resources/day_02/problem_2.inp")?;
-        let result = solve(&input)?;
+    #[test_log::test]
+    fn display_round_trip() -> Result<(), Box<dyn Error>> {
+        use super::Record;
 
-        println!("{result}");
+        for (path, input) in crate::resources::inputs(2)? {
+            let record: Record = input.parse()?;
+            assert_eq!(record.to_string().parse::<Record>()?, record, "{path:?}");
+        }
 
+        // Colors come out in a fixed order, so only sorted rounds print as read.
+        let game = "Game 3: 20 red, 8 green, 6 blue; 5 blue, 4 red, 13 green; 5 green, 1 red";
+        assert_eq!(
+            game.parse::<Record>()?.to_string(),
+            "Game 3: 20 red, 8 green, 6 blue; 4 red, 13 green, 5 blue; 1 red, 5 green"
+        );
         Ok(())
     }
 }
@@ -1,238 +1,476 @@
-use std::str::FromStr;
+//! Day 3: Gear Ratios. The model is an engine [`Schematic`] of
+//! [`PartNumber`]s and [`Symbol`]s.
+
+use std::{convert::Infallible, fmt, ops::RangeInclusive, str::FromStr};
 
 use anyhow::Result;
+use log::{debug, trace};
+
+use crate::parsers::{grid, parse};
 
-#[derive(PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum SchematicCell {
     Blank,
     Symbol(char),
+    // Index of the part number the digit belongs to.
     Number(usize),
 }
 
-#[derive(Default)]
-struct Schematic {
+/// A number in the schematic, spanning columns `start..=end` of its row.
+/// Whether it is really a part number depends on the symbols around it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartNumber {
+    row: usize,
+    start: usize,
+    end: usize,
+    value: usize,
+}
+
+impl PartNumber {
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn value(&self) -> usize {
+        self.value
+    }
+}
+
+/// Any cell that is neither a digit nor a `.`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Symbol {
+    row: usize,
+    col: usize,
+    symbol: char,
+}
+
+impl Symbol {
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    pub fn col(&self) -> usize {
+        self.col
+    }
+
+    pub fn symbol(&self) -> char {
+        self.symbol
+    }
+}
+
+/// Which cells around a symbol count as touching it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjacency {
+    /// Only the cells sharing a side.
+    Four,
+    /// Diagonals included.
+    Eight,
+}
+
+impl Adjacency {
+    fn offsets(&self) -> &'static [(isize, isize)] {
+        match self {
+            Adjacency::Four => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Adjacency::Eight => &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+        }
+    }
+}
+
+/// The engine schematic, with numbers as spans and symbols as points.
+// Every cell points back to what covers it so adjacency queries are a
+// constant number of lookups.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "Parts", try_from = "Parts")
+)]
+pub struct Schematic {
     width: usize,
     height: usize,
     cells: Vec<Vec<SchematicCell>>,
+    numbers: Vec<PartNumber>,
+    symbols: Vec<Symbol>,
 }
 
-#[derive(Default)]
-struct SchematicMap {
-    parts: Vec<(isize, isize, char)>,
-    part_numbers: Vec<(isize, isize, isize, usize)>,
+// A schematic without its cells, which are rebuilt when deserializing so they
+// can't disagree with the numbers and symbols.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Parts {
+    width: usize,
+    height: usize,
+    numbers: Vec<PartNumber>,
+    symbols: Vec<Symbol>,
 }
 
-impl FromStr for SchematicMap {
-    type Err = anyhow::Error;
+#[cfg(feature = "serde")]
+impl From<Schematic> for Parts {
+    fn from(schematic: Schematic) -> Parts {
+        Parts {
+            width: schematic.width,
+            height: schematic.height,
+            numbers: schematic.numbers,
+            symbols: schematic.symbols,
+        }
+    }
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = vec![];
-        let mut part_numbers = vec![];
-
-        let mut number = String::default();
-        let mut number_start = None;
-        for (row, line) in s.lines().enumerate() {
-            if let Some(start) = number_start {
-                part_numbers.push((
-                    row as isize,
-                    start as isize,
-                    (line.len() as isize) - 1,
-                    number.parse::<usize>()?,
-                ));
+#[cfg(feature = "serde")]
+impl TryFrom<Parts> for Schematic {
+    type Error = anyhow::Error;
+
+    fn try_from(parts: Parts) -> Result<Schematic> {
+        use anyhow::{bail, Context};
+
+        let mut cells = vec![vec![SchematicCell::Blank; parts.width]; parts.height];
+        let mut place = |row: usize, col: usize, cell| {
+            let slot = cells
+                .get_mut(row)
+                .and_then(|row| row.get_mut(col))
+                .with_context(|| format!("Cell {row},{col} is out of the schematic"))?;
+            if *slot != SchematicCell::Blank {
+                bail!("Cell {row},{col} is covered twice");
             }
-            number = String::default();
-            number_start = None;
-            for (col, cell) in line.chars().enumerate() {
-                match cell {
-                    cell if cell.is_ascii_digit() => {
-                        let _ = number_start.get_or_insert(col);
-                        number.push(cell);
-                    }
-                    cell => {
-                        if let Some(start) = number_start {
-                            part_numbers.push((
-                                row as isize,
-                                start as isize,
-                                (col as isize) - 1,
-                                number.parse::<usize>()?,
-                            ));
-                        }
-                        number = String::default();
-                        number_start = None;
+            *slot = cell;
+            Ok(())
+        };
+
+        for (index, number) in parts.numbers.iter().enumerate() {
+            // Leading zeros may take up more columns than the value's digits.
+            let digits = number.value.to_string().len();
+            if number.end < number.start || number.end + 1 - number.start < digits {
+                bail!("Number {} doesn't fit its columns", number.value);
+            }
+            for col in number.start..=number.end {
+                place(number.row, col, SchematicCell::Number(index))?;
+            }
+        }
+        for symbol in &parts.symbols {
+            if symbol.symbol == '.' || symbol.symbol.is_ascii_digit() {
+                bail!("{:?} is not a symbol", symbol.symbol);
+            }
+            place(symbol.row, symbol.col, SchematicCell::Symbol(symbol.symbol))?;
+        }
+
+        Ok(Schematic {
+            width: parts.width,
+            height: parts.height,
+            cells,
+            numbers: parts.numbers,
+            symbols: parts.symbols,
+        })
+    }
+}
+
+impl FromStr for Schematic {
+    type Err = anyhow::Error;
 
-                        if cell != '.' {
-                            parts.push((row as isize, col as isize, cell));
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut schematic = Schematic::default();
+
+        let rows = parse(s, grid(Ok::<char, Infallible>))?;
+        for (row, line) in rows.iter().enumerate() {
+            let mut cells = Vec::with_capacity(line.len());
+            for (col, &cell) in line.iter().enumerate() {
+                if let Some(digit) = cell.to_digit(10) {
+                    match cells.last() {
+                        Some(&SchematicCell::Number(index)) => {
+                            let number: &mut PartNumber = &mut schematic.numbers[index];
+                            number.end = col;
+                            number.value = number.value * 10 + digit as usize;
                         }
+                        _ => schematic.numbers.push(PartNumber {
+                            row,
+                            start: col,
+                            end: col,
+                            value: digit as usize,
+                        }),
                     }
+                    cells.push(SchematicCell::Number(schematic.numbers.len() - 1));
+                } else if cell != '.' {
+                    schematic.symbols.push(Symbol {
+                        row,
+                        col,
+                        symbol: cell,
+                    });
+                    cells.push(SchematicCell::Symbol(cell));
+                } else {
+                    cells.push(SchematicCell::Blank);
                 }
             }
+            schematic.cells.push(cells);
+            schematic.height = row + 1;
+            schematic.width = std::cmp::max(schematic.width, line.len());
         }
 
-        Ok(SchematicMap {
-            parts,
-            part_numbers,
-        })
+        debug!(
+            "Parsed schematic of {}x{} with {} symbols and {} numbers",
+            schematic.height,
+            schematic.width,
+            schematic.symbols.len(),
+            schematic.numbers.len()
+        );
+
+        Ok(schematic)
+    }
+}
+
+impl fmt::Display for Schematic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (row, cells) in self.cells.iter().enumerate() {
+            if row > 0 {
+                writeln!(f)?;
+            }
+            for (col, cell) in cells.iter().enumerate() {
+                match *cell {
+                    SchematicCell::Blank => write!(f, ".")?,
+                    SchematicCell::Symbol(symbol) => write!(f, "{symbol}")?,
+                    SchematicCell::Number(index) => {
+                        let number = &self.numbers[index];
+                        // Padded in case the number was written with leading
+                        // zeros.
+                        let digits = format!(
+                            "{:0width$}",
+                            number.value,
+                            width = number.end + 1 - number.start
+                        );
+                        write!(f, "{}", &digits[col - number.start..][..1])?;
+                    }
+                }
+            }
+        }
+        Ok(())
     }
 }
 
 impl Schematic {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Every number in reading order, part number or not.
+    pub fn numbers(&self) -> &[PartNumber] {
+        &self.numbers
+    }
+
+    /// Every symbol in reading order.
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
     fn get(&self, row: isize, col: isize) -> Option<&SchematicCell> {
         let row: usize = row.try_into().ok()?;
         let col: usize = col.try_into().ok()?;
         self.cells.get(row).and_then(|row| row.get(col))
     }
-}
 
-fn parse_schematic(schematic: &str) -> Result<Schematic> {
-    let mut parsed_schematic = Schematic::default();
-
-    for (row, line) in schematic.lines().enumerate() {
-        let mut parsed_schematic_row = Vec::with_capacity(line.len());
-        for c in line.chars() {
-            if c.is_ascii_digit() {
-                parsed_schematic_row.push(SchematicCell::Number(format!("{c}").parse::<usize>()?));
-            } else if c != '.' {
-                parsed_schematic_row.push(SchematicCell::Symbol(c));
-            } else {
-                parsed_schematic_row.push(SchematicCell::Blank);
+    /// The number with a digit at `row`, `col`, if any.
+    pub fn number_at(&self, row: usize, col: usize) -> Option<&PartNumber> {
+        match self.cells.get(row)?.get(col)? {
+            &SchematicCell::Number(index) => Some(&self.numbers[index]),
+            _ => None,
+        }
+    }
+
+    /// The numbers with a digit in a cell around `row`, `col`, each once, in
+    /// the order of `adjacency`'s offsets.
+    pub fn numbers_around(&self, row: usize, col: usize, adjacency: Adjacency) -> Vec<&PartNumber> {
+        let mut indices = Vec::new();
+        for (dr, dc) in adjacency.offsets() {
+            let (r, c) = (row as isize + dr, col as isize + dc);
+            if let Some(&SchematicCell::Number(index)) = self.get(r, c) {
+                if !indices.contains(&index) {
+                    indices.push(index);
+                }
             }
         }
-        parsed_schematic.cells.push(parsed_schematic_row);
-        parsed_schematic.height = row + 1;
-        parsed_schematic.width = std::cmp::max(parsed_schematic.width, line.len());
+        indices
+            .into_iter()
+            .map(|index| &self.numbers[index])
+            .collect()
     }
 
-    Ok(parsed_schematic)
-}
+    /// Whether a symbol surrounds `number`, diagonals included.
+    pub fn touches_symbol(&self, number: &PartNumber) -> bool {
+        let (row, start, end) = (
+            number.row as isize,
+            number.start as isize,
+            number.end as isize,
+        );
+        (row - 1..=row + 1).any(|r| {
+            (start - 1..=end + 1).any(|c| matches!(self.get(r, c), Some(SchematicCell::Symbol(_))))
+        })
+    }
 
-pub mod problem_1 {
+    /// Numbers touching a symbol.
+    pub fn part_numbers(&self) -> impl Iterator<Item = &PartNumber> {
+        self.numbers
+            .iter()
+            .filter(|number| self.touches_symbol(number))
+    }
 
-    use super::{parse_schematic, Schematic, SchematicCell};
-    use anyhow::Result;
+    pub fn solve_1(&self) -> usize {
+        let sum = self
+            .part_numbers()
+            .inspect(|number| trace!("Part number {number:?}"))
+            .map(|number| number.value)
+            .sum();
 
-    #[allow(dead_code)]
-    fn get_number(schematic: &Schematic, row: isize, col: isize) -> Option<usize> {
-        // If its not a number return none.
-        match schematic.get(row, col) {
-            Some(SchematicCell::Number(_)) => (),
-            _ => return None,
-        }
+        debug!("Sum of part numbers: {sum}");
+        sum
+    }
 
-        let mut num_start = col;
-        while let Some(SchematicCell::Number(_)) = schematic.get(row, num_start - 1) {
-            num_start -= 1;
-        }
+    /// Every gear in the schematic according to `rule`, in reading order.
+    pub fn gears(&self, rule: &GearRule) -> Vec<Gear> {
+        self.symbols
+            .iter()
+            .filter(|symbol| rule.symbols.contains(&symbol.symbol))
+            .filter_map(|symbol| {
+                let part_numbers: Vec<usize> = self
+                    .numbers_around(symbol.row, symbol.col, rule.adjacency)
+                    .into_iter()
+                    .map(PartNumber::value)
+                    .collect();
+
+                rule.part_numbers
+                    .contains(&part_numbers.len())
+                    .then(|| Gear {
+                        row: symbol.row,
+                        col: symbol.col,
+                        symbol: symbol.symbol,
+                        ratio: rule.aggregation.aggregate(&part_numbers),
+                        part_numbers,
+                    })
+            })
+            .inspect(|gear| trace!("{gear}"))
+            .collect()
+    }
 
-        let mut number = 0usize;
-        while let Some(SchematicCell::Number(ref d)) = schematic.get(row, num_start) {
-            number = number * 10 + d;
-            num_start += 1;
-        }
+    pub fn solve_2_with(&self, rule: &GearRule) -> usize {
+        let gears = self.gears(rule).into_iter().map(|gear| gear.ratio).sum();
 
-        Some(number)
+        debug!("Sum of gear ratios: {gears}");
+        gears
     }
 
-    fn touches_symbol(schematic: &Schematic, col: usize, row: usize) -> bool {
-        let mut col_s = (col as isize) - 1;
-        let mut row_s = (row as isize) - 1;
+    pub fn solve_2(&self) -> usize {
+        self.solve_2_with(&GearRule::default())
+    }
+}
 
-        let col_e = (col as isize) + 1;
-        let row_e = (row as isize) + 1;
+/// How the part numbers around a gear combine into its ratio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    Product,
+    Sum,
+    Max,
+}
 
-        loop {
-            if row_s > row_e {
-                break;
-            }
-            loop {
-                if col_s > col_e {
-                    break;
-                }
-                if let Some(SchematicCell::Symbol(_)) = schematic.get(row_s, col_s) {
-                    return true;
-                }
-                col_s += 1;
-            }
-            row_s += 1;
-            col_s = (col as isize) - 1;
+impl Aggregation {
+    fn aggregate(&self, part_numbers: &[usize]) -> usize {
+        match self {
+            Aggregation::Product => part_numbers.iter().product(),
+            Aggregation::Sum => part_numbers.iter().sum(),
+            Aggregation::Max => part_numbers.iter().copied().max().unwrap_or(0),
         }
-
-        false
     }
+}
 
-    pub fn solve(input: &str) -> Result<usize> {
-        let schematic = parse_schematic(input)?;
-
-        let mut sum: usize = 0;
+/// What makes a symbol a gear. The default is the puzzle's: a `*` touching
+/// exactly two part numbers, diagonals included, multiplied together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GearRule {
+    pub symbols: Vec<char>,
+    pub part_numbers: RangeInclusive<usize>,
+    pub aggregation: Aggregation,
+    pub adjacency: Adjacency,
+}
 
-        enum State {
-            Number(usize),
-            Part(usize),
-            Other(usize),
+impl Default for GearRule {
+    fn default() -> Self {
+        GearRule {
+            symbols: vec!['*'],
+            part_numbers: 2..=2,
+            aggregation: Aggregation::Product,
+            adjacency: Adjacency::Eight,
         }
+    }
+}
 
-        let mut state = State::Other(0);
-        for (row, line) in schematic.cells.iter().enumerate() {
-            for (col, cell) in line.iter().enumerate() {
-                match cell {
-                    SchematicCell::Number(ref d) => {
-                        state = match state {
-                            State::Number(n) | State::Other(n) => {
-                                if touches_symbol(&schematic, col, row) {
-                                    State::Part(n * 10 + d)
-                                } else {
-                                    State::Number(n * 10 + d)
-                                }
-                            }
-                            State::Part(n) => State::Part(n * 10 + d),
-                        };
-                    }
-                    _ => {
-                        if let State::Part(n) = state {
-                            sum += n;
-                        }
-                        state = State::Other(0)
-                    }
-                }
-            }
-            if let State::Part(n) = state {
-                sum += n;
-            }
-            state = State::Other(0)
-        }
-        if let State::Part(n) = state {
-            sum += n;
-        }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gear {
+    pub row: usize,
+    pub col: usize,
+    pub symbol: char,
+    pub part_numbers: Vec<usize>,
+    pub ratio: usize,
+}
 
-        Ok(sum)
+impl fmt::Display for Gear {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at ({}, {}) with part numbers {:?}: {}",
+            self.symbol, self.row, self.col, self.part_numbers, self.ratio
+        )
     }
 }
 
-pub mod problem_2 {
-    use super::SchematicMap;
+pub mod problem_1 {
+
+    use super::Schematic;
     use anyhow::Result;
 
     pub fn solve(input: &str) -> Result<usize> {
-        let map: SchematicMap = input.parse()?;
+        let schematic: Schematic = input.parse()?;
+        Ok(schematic.solve_1())
+    }
+}
+
+pub mod problem_2 {
 
-        let mut gears = 0;
+    pub use super::{Aggregation, Gear, GearRule};
 
-        for (row, col, _) in map.parts.iter().filter(|(_, _, part)| *part == '*') {
-            let mut part = Vec::new();
-            for part_number in map.part_numbers.iter() {
-                let within_row_range = *row >= part_number.0 - 1 && *row <= part_number.0 + 1;
-                let within_col_range = *col >= part_number.1 - 1 && *col <= part_number.2 + 1;
+    use super::Schematic;
+    use anyhow::Result;
 
-                if within_row_range && within_col_range {
-                    part.push(part_number.3);
-                }
-            }
+    /// Every gear in the schematic according to `rule`, in reading order.
+    pub fn report(input: &str, rule: &GearRule) -> Result<Vec<Gear>> {
+        let schematic: Schematic = input.parse()?;
+        Ok(schematic.gears(rule))
+    }
 
-            if part.len() == 2 {
-                gears += part.into_iter().reduce(|prod, num| prod * num).unwrap();
-            }
-        }
+    pub fn solve_with(input: &str, rule: &GearRule) -> Result<usize> {
+        let schematic: Schematic = input.parse()?;
+        Ok(schematic.solve_2_with(rule))
+    }
 
-        Ok(gears)
+    pub fn solve(input: &str) -> Result<usize> {
+        solve_with(input, &GearRule::default())
     }
 }
 
@@ -242,42 +480,109 @@ mod test {
     use std::error::Error;
     use std::fs::read_to_string;
 
-    const P1_TRAIN_SOLUTION: usize = 4361;
-    const P2_TRAIN_SOLUTION: usize = 467835;
-
-    #[test]
-    fn train_problem_1() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
-        let input = read_to_string("resources/day_03/train_problem_1.inp")?;
-        let result = solve(&input)?;
-        assert_eq!(result, P1_TRAIN_SOLUTION);
+    #[test_log::test]
+    fn numbers_at_line_end() -> Result<(), Box<dyn Error>> {
+        use super::{problem_1, problem_2, Adjacency, PartNumber, Schematic};
+        let input = read_to_string("resources/day_03/train_line_end.inp")?;
+        let schematic: Schematic = input.parse()?;
+        let values = |numbers: Vec<&PartNumber>| -> Vec<usize> {
+            numbers.into_iter().map(PartNumber::value).collect()
+        };
+        assert_eq!(
+            values(schematic.numbers_around(1, 4, Adjacency::Eight)),
+            vec![12, 34]
+        );
+        assert_eq!(
+            values(schematic.numbers_around(1, 4, Adjacency::Four)),
+            vec![12]
+        );
+        assert_eq!(problem_1::solve(&input)?, 46);
+        assert_eq!(problem_2::solve(&input)?, 408);
         Ok(())
     }
 
-    #[test]
-    fn solve_problem_1() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
-        let input = read_to_string("resources/day_03/problem_1.inp")?;
-        let result = solve(&input)?;
-        println!("{result}");
+    #[test_log::test]
+    fn adjacency_queries() -> Result<(), Box<dyn Error>> {
+        use super::{Adjacency, Schematic};
+
+        let schematic: Schematic =
+            read_to_string("resources/day_03/train_problem_1.inp")?.parse()?;
+        let number = schematic.number_at(0, 1).map(|number| number.value());
+        assert_eq!(number, Some(467));
+        assert_eq!(
+            schematic.number_at(0, 5).map(|number| number.start()),
+            Some(5)
+        );
+        assert_eq!(schematic.number_at(1, 3), None);
+        assert_eq!(schematic.number_at(0, 10), None);
+        assert_eq!(schematic.number_at(10, 0), None);
+
+        // Around a blank cell, and from the left edge without wrapping.
+        let around: Vec<_> = schematic
+            .numbers_around(1, 1, Adjacency::Eight)
+            .into_iter()
+            .map(|number| number.value())
+            .collect();
+        assert_eq!(around, vec![467, 35]);
+        assert_eq!(schematic.numbers_around(1, 0, Adjacency::Four).len(), 1);
+        // Around a digit, the other digits of its number count too.
+        let around = schematic.numbers_around(0, 1, Adjacency::Eight);
+        assert_eq!(around, vec![schematic.number_at(0, 0).unwrap()]);
         Ok(())
     }
 
-    #[test]
-    fn train_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_2::solve;
+    #[test_log::test]
+    fn gear_rules() -> Result<(), Box<dyn Error>> {
+        use super::problem_2::{report, solve_with, Aggregation, GearRule};
         let input = read_to_string("resources/day_03/train_problem_2.inp")?;
-        let result = solve(&input)?;
-        assert_eq!(result, P2_TRAIN_SOLUTION);
+
+        let gears = report(&input, &GearRule::default())?;
+        let ratios: Vec<usize> = gears.iter().map(|gear| gear.ratio).collect();
+        assert_eq!(ratios, vec![16345, 451490]);
+        assert_eq!(gears[0].part_numbers, vec![467, 35]);
+
+        let rule = GearRule {
+            part_numbers: 1..=2,
+            aggregation: Aggregation::Max,
+            ..Default::default()
+        };
+        assert_eq!(solve_with(&input, &rule)?, 467 + 617 + 755);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn schematic() -> Result<(), Box<dyn Error>> {
+        use super::Schematic;
+
+        let schematic: Schematic =
+            read_to_string("resources/day_03/train_problem_1.inp")?.parse()?;
+        assert_eq!((schematic.height(), schematic.width()), (10, 10));
+        let first = &schematic.numbers()[0];
+        assert_eq!(
+            (first.row(), first.start(), first.end(), first.value()),
+            (0, 0, 2, 467)
+        );
+        let symbol = &schematic.symbols()[0];
+        assert_eq!((symbol.row(), symbol.col(), symbol.symbol()), (1, 3, '*'));
+        assert_eq!(
+            schematic.part_numbers().count(),
+            schematic.numbers().len() - 2
+        );
+        assert_eq!(schematic.solve_1(), 4361);
+        assert_eq!(schematic.solve_2(), 467835);
         Ok(())
     }
 
-    #[test]
-    fn solve_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_2::solve;
-        let input = read_to_string("resources/day_03/problem_2.inp")?;
-        let result = solve(&input)?;
-        println!("{result}");
+    #[test_log::test]
+    fn display_round_trip() -> Result<(), Box<dyn Error>> {
+        use super::Schematic;
+
+        for (path, input) in crate::resources::inputs(3)? {
+            let schematic: Schematic = input.parse()?;
+            assert_eq!(schematic.to_string(), input.trim_end(), "{path:?}");
+            assert_eq!(schematic.to_string().parse::<Schematic>()?, schematic);
+        }
+        assert_eq!("..007*".parse::<Schematic>()?.to_string(), "..007*");
         Ok(())
     }
 }
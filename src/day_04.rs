@@ -1,105 +1,644 @@
-use std::{collections::HashSet, str::FromStr};
+//! Day 4: Scratchcards. The model is a [`Pile`] of [`Card`]s.
 
-use anyhow::Context;
-use nom::{
-    bytes::complete::{tag, take_while},
-    sequence::{delimited, tuple},
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+    str::FromStr,
 };
 
-#[derive(Debug)]
-struct Card {
-    numbers: HashSet<usize>,
-    winning_numbers: HashSet<usize>,
-    copies: usize,
+use anyhow::{bail, Result};
+use log::{debug, trace};
+use nom::{bytes::complete::tag, character::complete::space1, sequence::tuple};
+
+use crate::parsers::{header, lines, numbers, parse, IResult};
+
+/// A scratchcard, with its numbers as written even if repeated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Card {
+    id: usize,
+    winning_numbers: Vec<usize>,
+    numbers: Vec<usize>,
+}
+
+impl Card {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn winning_numbers(&self) -> &[usize] {
+        &self.winning_numbers
+    }
+
+    pub fn numbers(&self) -> &[usize] {
+        &self.numbers
+    }
+
+    pub fn points(&self) -> usize {
+        points(self.wins())
+    }
+
+    /// How many of the numbers are winning numbers.
+    pub fn wins(&self) -> usize {
+        let winning_numbers: HashSet<_> = self.winning_numbers.iter().collect();
+        self.numbers
+            .iter()
+            .collect::<HashSet<_>>()
+            .intersection(&winning_numbers)
+            .count()
+    }
+
+    // Writes the card with its id right aligned to `id_width`, and every
+    // number to two columns as the puzzle does.
+    fn write(&self, f: &mut fmt::Formatter<'_>, id_width: usize) -> fmt::Result {
+        write!(f, "Card {:>id_width$}:", self.id)?;
+        for number in &self.winning_numbers {
+            write!(f, " {number:>2}")?;
+        }
+        write!(f, " |")?;
+        for number in &self.numbers {
+            write!(f, " {number:>2}")?;
+        }
+        Ok(())
+    }
+
+    fn side(&self, side: Side) -> &[usize] {
+        match side {
+            Side::Winning => &self.winning_numbers,
+            Side::Mine => &self.numbers,
+        }
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
 }
 
 impl FromStr for Card {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parse_card_id = delimited(
-            tuple((
-                tag::<_, _, nom::error::Error<_>>("Card"),
-                take_while(|c: char| c.is_whitespace()),
-            )),
-            take_while(|c: char| c.is_ascii_digit()),
-            tag(":"),
-        );
-        let (numbers, card) = parse_card_id(s).unwrap();
-        let _ = card.parse::<usize>()?;
-
-        let (winning, mine) = numbers
-            .split_once('|')
-            .context("Every line must have one |")?;
-        let winning_numbers: HashSet<usize> = winning
-            .split_whitespace()
-            .map(|n| n.parse::<usize>().unwrap())
-            .collect();
-        let numbers: HashSet<usize> = mine
-            .split_whitespace()
-            .map(|n| n.parse::<usize>().unwrap())
-            .collect();
-
-        Ok(Card {
-            numbers,
+        parse(s, card)
+    }
+}
+
+// `Card N: ` then the winning numbers and the numbers, split by `|`.
+fn card(input: &str) -> IResult<'_, Card> {
+    let (input, (id, winning_numbers, _, numbers)) = tuple((
+        header("Card"),
+        numbers,
+        tuple((space1, tag("|"), space1)),
+        numbers,
+    ))(input)?;
+    trace!(
+        "Parsed card {id} with {} winning numbers and {} numbers",
+        winning_numbers.len(),
+        numbers.len()
+    );
+    Ok((
+        input,
+        Card {
+            id,
             winning_numbers,
-            copies: 1,
-        })
+            numbers,
+        },
+    ))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Winning,
+    Mine,
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Side::Winning => write!(f, "winning numbers"),
+            Side::Mine => write!(f, "numbers"),
+        }
+    }
+}
+
+/// Something wrong with an input that still parses. Lines are numbered from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    UnexpectedId {
+        line: usize,
+        id: usize,
+        expected: usize,
+    },
+    /// An id no greater than the one before it. `first_line` is where the
+    /// card would be had every id so far counted up one per line, which is
+    /// exact unless an [`Issue::UnexpectedId`] was found earlier.
+    DuplicateId {
+        line: usize,
+        id: usize,
+        first_line: usize,
+    },
+    DuplicateNumber {
+        line: usize,
+        side: Side,
+        number: usize,
+    },
+    UnexpectedCount {
+        line: usize,
+        side: Side,
+        count: usize,
+        expected: usize,
+    },
+    WinsOverflow {
+        line: usize,
+        id: usize,
+        wins: usize,
+        missing: usize,
+    },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::UnexpectedId { id, expected, .. } => {
+                write!(f, "card {id} should be card {expected}")
+            }
+            Issue::DuplicateId { id, first_line, .. } => {
+                write!(f, "card {id} already appeared on line {first_line}")
+            }
+            Issue::DuplicateNumber { side, number, .. } => {
+                write!(f, "{number} is repeated in the {side}")
+            }
+            Issue::UnexpectedCount {
+                side,
+                count,
+                expected,
+                ..
+            } => write!(f, "{count} {side} where other cards have {expected}"),
+            Issue::WinsOverflow {
+                id, wins, missing, ..
+            } => write!(
+                f,
+                "card {id} wins {wins} cards but {missing} of them don't exist"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Issue {}
+
+impl Issue {
+    /// Whether the points of part 1 can't be trusted. Only ids that repeat or
+    /// skip do that: wins past the end only matter to the copies of part 2.
+    pub fn breaks_points(&self) -> bool {
+        matches!(self, Issue::UnexpectedId { .. } | Issue::DuplicateId { .. })
+    }
+
+    /// Whether the copies of part 2 can't be counted, which wins past the
+    /// last card do on top of bad ids. Repeated numbers and odd counts are
+    /// only reported, see [`Pile::validate`].
+    pub fn breaks_copies(&self) -> bool {
+        self.breaks_points() || matches!(self, Issue::WinsOverflow { .. })
+    }
+
+    pub fn line(&self) -> usize {
+        match self {
+            Issue::UnexpectedId { line, .. }
+            | Issue::DuplicateId { line, .. }
+            | Issue::DuplicateNumber { line, .. }
+            | Issue::UnexpectedCount { line, .. }
+            | Issue::WinsOverflow { line, .. } => *line,
+        }
+    }
+}
+
+/// Every scratchcard, in the order they are stacked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pile {
+    cards: Vec<Card>,
+}
+
+impl FromStr for Pile {
+    type Err = anyhow::Error;
+
+    /// Parses every card without checking the pile as a whole, see
+    /// [`Pile::validate`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cards = parse(s, lines(card))?;
+        Ok(Pile { cards })
+    }
+}
+
+/// Lines up every card as the puzzle does, ids padded to the longest one.
+impl fmt::Display for Pile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let id_width = self
+            .cards
+            .iter()
+            .map(|card| card.id.to_string().len())
+            .max()
+            .unwrap_or(0);
+        for (i, card) in self.cards.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            card.write(f, id_width)?;
+        }
+        Ok(())
+    }
+}
+
+impl Pile {
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    /// Every problem found in the pile, by line.
+    pub fn validate(&self) -> Vec<Issue> {
+        check(&self.cards)
+    }
+
+    // Solvers only trust piles without the issues that break them.
+    fn ensure_valid(&self, breaking: fn(&Issue) -> bool) -> Result<()> {
+        let issues: Vec<_> = self.validate().into_iter().filter(breaking).collect();
+        if !issues.is_empty() {
+            let issues: Vec<_> = issues
+                .iter()
+                .map(|issue| format!("line {}: {issue}", issue.line()))
+                .collect();
+            bail!("Invalid scratchcards:\n{}", issues.join("\n"));
+        }
+        Ok(())
+    }
+
+    pub fn solve_1(&self) -> Result<usize> {
+        self.ensure_valid(Issue::breaks_points)?;
+        let points = self.cards.iter().map(Card::points).sum();
+        debug!("Total points: {points}");
+        Ok(points)
+    }
+
+    pub fn solve_2(&self) -> Result<usize> {
+        let total = self.cascade()?.total();
+        debug!("Total scratchcards: {total}");
+        Ok(total)
+    }
+
+    pub fn cascade(&self) -> Result<problem_2::Cascade> {
+        self.ensure_valid(Issue::breaks_copies)?;
+        Ok(problem_2::Cascade::from_wins(
+            self.cards.iter().map(Card::wins).collect(),
+        ))
+    }
+}
+
+fn duplicate_numbers(line: usize, card: &Card) -> Vec<Issue> {
+    let mut issues = vec![];
+    for side in [Side::Winning, Side::Mine] {
+        let mut numbers = HashSet::new();
+        for &number in card.side(side) {
+            if !numbers.insert(number) {
+                issues.push(Issue::DuplicateNumber { line, side, number });
+            }
+        }
+    }
+    issues
+}
+
+// Checks one card at a time, against the cards before it. Ids must count up
+// from 1 and every card must have as many numbers as the first one, which is
+// all that can be told before the whole pile is read. Nothing is kept about
+// cards that can no longer win copies, so streams take bounded memory.
+#[derive(Default)]
+struct Checker {
+    line: usize,
+    // The greatest id so far and the line it was on.
+    last_id: usize,
+    last_line: usize,
+    counts: Option<[usize; 2]>,
+    // Cards still winning copies past the last line read, as (line, id, wins).
+    reaching: VecDeque<(usize, usize, usize)>,
+}
+
+impl Checker {
+    // Issues with the next card, which has `wins` winning numbers.
+    fn card(&mut self, card: &Card, wins: usize) -> Vec<Issue> {
+        self.line += 1;
+        let line = self.line;
+        let mut issues = vec![];
+
+        if card.id <= self.last_id {
+            issues.push(Issue::DuplicateId {
+                line,
+                id: card.id,
+                first_line: self.last_line.saturating_sub(self.last_id - card.id).max(1),
+            });
+        } else {
+            let expected = self.last_id + 1;
+            if card.id != expected {
+                issues.push(Issue::UnexpectedId {
+                    line,
+                    id: card.id,
+                    expected,
+                });
+            }
+            self.last_id = card.id;
+            self.last_line = line;
+        }
+
+        issues.extend(duplicate_numbers(line, card));
+
+        let counts = [card.winning_numbers.len(), card.numbers.len()];
+        let expected = *self.counts.get_or_insert(counts);
+        for (side, (count, expected)) in [Side::Winning, Side::Mine]
+            .into_iter()
+            .zip(counts.into_iter().zip(expected))
+        {
+            if count != expected {
+                issues.push(Issue::UnexpectedCount {
+                    line,
+                    side,
+                    count,
+                    expected,
+                });
+            }
+        }
+
+        self.reaching.retain(|&(from, _, wins)| from + wins > line);
+        if wins > 0 {
+            self.reaching.push_back((line, card.id, wins));
+        }
+        issues
+    }
+
+    // Cards that win copies of cards past the last one.
+    fn finish(&self) -> Vec<Issue> {
+        self.reaching
+            .iter()
+            .map(|&(line, id, wins)| Issue::WinsOverflow {
+                line,
+                id,
+                wins,
+                missing: line + wins - self.line,
+            })
+            .collect()
+    }
+}
+
+fn check(cards: &[Card]) -> Vec<Issue> {
+    let mut checker = Checker::default();
+    let mut issues: Vec<_> = cards
+        .iter()
+        .flat_map(|card| checker.card(card, card.wins()))
+        .collect();
+    issues.extend(checker.finish());
+    issues.sort_by_key(Issue::line);
+    issues
+}
+
+/// Lists every problem found in a deck of scratchcards, failing only if a
+/// line can't be parsed at all.
+pub fn validate(input: &str) -> Result<Vec<Issue>> {
+    Ok(input.parse::<Pile>()?.validate())
+}
+
+// The checks of [`validate`] made while streaming, stopping at the first
+// issue that is `breaking`.
+struct Stream {
+    checker: Checker,
+    breaking: fn(&Issue) -> bool,
+}
+
+impl Stream {
+    fn new(breaking: fn(&Issue) -> bool) -> Stream {
+        Stream {
+            checker: Checker::default(),
+            breaking,
+        }
+    }
+
+    fn wins(&mut self, text: &str) -> Result<usize> {
+        let card = text.parse::<Card>()?;
+        let wins = card.wins();
+        let mut issues = self.checker.card(&card, wins).into_iter();
+        if let Some(issue) = issues.find(self.breaking) {
+            return Err(issue.into());
+        }
+        Ok(wins)
+    }
+
+    fn finish(self) -> Result<()> {
+        if let Some(issue) = self.checker.finish().into_iter().find(self.breaking) {
+            let line = issue.line();
+            return Err(anyhow::Error::new(issue).context(format!("Line {line}")));
+        }
+        Ok(())
+    }
+}
+
+fn points(wins: usize) -> usize {
+    if wins > 0 {
+        2usize.pow((wins as u32) - 1)
+    } else {
+        0
     }
 }
 
 pub mod problem_1 {
-    use super::Card;
+    use std::io::BufRead;
+
+    use super::{points, Issue, Pile, Stream};
+    use crate::streaming::try_fold_lines;
     use anyhow::Result;
-    use std::collections::HashSet;
+    use log::debug;
 
     pub fn solve(input: &str) -> Result<usize> {
-        Ok(input
-            .lines()
-            .map(|line| line.parse::<Card>().unwrap())
-            .map(|card| {
-                let wins = card
-                    .numbers
-                    .intersection(&card.winning_numbers)
-                    .collect::<HashSet<_>>()
-                    .len();
-
-                if wins > 0 {
-                    2usize.pow((wins as u32) - 1)
-                } else {
-                    0
-                }
-            })
-            .sum())
+        input.parse::<Pile>()?.solve_1()
+    }
+
+    /// Scores cards as they are read, stopping only at ids that repeat or
+    /// skip.
+    pub fn solve_reader<R: BufRead>(reader: R) -> Result<usize> {
+        let mut stream = Stream::new(Issue::breaks_points);
+        let total = try_fold_lines(reader, 0, |total, line| {
+            Ok(total + points(stream.wins(line)?))
+        })?;
+        stream.finish()?;
+        debug!("Total points: {total}");
+        Ok(total)
     }
 }
 
 pub mod problem_2 {
-    use super::Card;
+    use std::{collections::VecDeque, io::BufRead, ops::Range};
+
+    use super::{Issue, Pile, Stream};
+    use crate::streaming::try_fold_lines;
     use anyhow::Result;
-    use std::collections::HashSet;
+    use log::{debug, trace};
 
-    pub fn solve(input: &str) -> Result<usize> {
-        let mut cards: Vec<Card> = input
-            .lines()
-            .map(|line| line.parse::<Card>().unwrap())
-            .collect();
-
-        for card_i in 0..cards.len() {
-            let wins = cards[card_i]
-                .numbers
-                .intersection(&cards[card_i].winning_numbers)
-                .collect::<HashSet<_>>()
-                .len();
-
-            let copy_i_s = card_i + 1;
-            let copy_i_e = copy_i_s + wins;
-            for copy_i in copy_i_s..copy_i_e {
-                cards[copy_i].copies += cards[card_i].copies;
+    /// One card being scored while replaying the cascade. Cards are numbered
+    /// from 1 as in the puzzle.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Step {
+        pub card: usize,
+        pub copies: usize,
+        /// Cards that get one more copy for every copy of `card`.
+        pub won: Range<usize>,
+    }
+
+    /// Replays the cascade one card at a time, exposing the copies held so
+    /// far between steps.
+    pub struct Replay<'a> {
+        wins: &'a [usize],
+        copies: Vec<usize>,
+        next: usize,
+    }
+
+    impl Replay<'_> {
+        /// Copies of every card so far, indexed by card number minus one.
+        pub fn copies(&self) -> &[usize] {
+            &self.copies
+        }
+    }
+
+    impl Iterator for Replay<'_> {
+        type Item = Step;
+
+        fn next(&mut self) -> Option<Step> {
+            let card = self.next;
+            let wins = *self.wins.get(card)?;
+            self.next += 1;
+
+            // Wins past the last card are lost.
+            let won = card + 1..(card + 1 + wins).min(self.wins.len());
+            for copy in won.clone() {
+                self.copies[copy] += self.copies[card];
             }
+
+            Some(Step {
+                card: card + 1,
+                copies: self.copies[card],
+                won: won.start + 1..won.end + 1,
+            })
         }
+    }
 
-        Ok(cards.into_iter().fold(0, |sum, card| sum + card.copies))
+    /// The whole cascade of scratchcards, remembering where every copy came
+    /// from.
+    pub struct Cascade {
+        wins: Vec<usize>,
+        copies: Vec<usize>,
+        // For every card, the earlier cards it got copies from and how many.
+        provenance: Vec<Vec<(usize, usize)>>,
+    }
+
+    impl Cascade {
+        pub fn new(input: &str) -> Result<Cascade> {
+            input.parse::<Pile>()?.cascade()
+        }
+
+        pub(super) fn from_wins(wins: Vec<usize>) -> Cascade {
+            debug!("Playing {} cards", wins.len());
+
+            let mut provenance = vec![vec![]; wins.len()];
+            let mut replay = Replay {
+                wins: &wins,
+                copies: vec![1; wins.len()],
+                next: 0,
+            };
+            for step in replay.by_ref() {
+                trace!("{step:?}");
+                for copy in step.won {
+                    provenance[copy - 1].push((step.card, step.copies));
+                }
+            }
+            let copies = replay.copies;
+
+            Cascade {
+                wins,
+                copies,
+                provenance,
+            }
+        }
+
+        pub fn replay(&self) -> Replay<'_> {
+            Replay {
+                wins: &self.wins,
+                copies: vec![1; self.wins.len()],
+                next: 0,
+            }
+        }
+
+        pub fn total(&self) -> usize {
+            self.copies.iter().sum()
+        }
+
+        pub fn copies(&self, card: usize) -> Option<usize> {
+            self.copies.get(card.checked_sub(1)?).copied()
+        }
+
+        /// Earlier cards that won copies of `card`, with how many each.
+        pub fn provenance(&self, card: usize) -> Option<&[(usize, usize)]> {
+            self.provenance
+                .get(card.checked_sub(1)?)
+                .map(|sources| sources.as_slice())
+        }
+
+        // Copies descending from the original of each card, itself included,
+        // treating `removed` as if it were not on the table.
+        fn descendants(&self, removed: Option<usize>) -> Vec<usize> {
+            let mut descendants = vec![0; self.wins.len()];
+            for card in (0..self.wins.len()).rev() {
+                if Some(card + 1) == removed {
+                    continue;
+                }
+                let won = card + 1..(card + 1 + self.wins[card]).min(self.wins.len());
+                descendants[card] = 1 + won.map(|copy| descendants[copy]).sum::<usize>();
+            }
+            descendants
+        }
+
+        /// Original card whose wins end up producing the most copies, with
+        /// the number of copies it produces.
+        pub fn top_contributor(&self) -> Option<(usize, usize)> {
+            self.descendants(None)
+                .into_iter()
+                .enumerate()
+                .map(|(card, descendants)| (card + 1, descendants - 1))
+                .max_by_key(|&(card, copies)| (copies, std::cmp::Reverse(card)))
+        }
+
+        /// Total cards held if `card` was taken off the table before playing.
+        pub fn total_without(&self, card: usize) -> usize {
+            self.descendants(Some(card)).into_iter().sum()
+        }
+    }
+
+    pub fn solve(input: &str) -> Result<usize> {
+        input.parse::<Pile>()?.solve_2()
+    }
+
+    /// Counts copies as cards are read, keeping only the copies already won
+    /// for the next few cards.
+    pub fn solve_reader<R: BufRead>(reader: R) -> Result<usize> {
+        let mut stream = Stream::new(Issue::breaks_copies);
+        let mut pending = VecDeque::new();
+        let total = try_fold_lines(reader, 0, |total, line| {
+            let wins = stream.wins(line)?;
+            let copies = 1 + pending.pop_front().unwrap_or(0);
+            if pending.len() < wins {
+                pending.resize(wins, 0);
+            }
+            for won in pending.iter_mut().take(wins) {
+                *won += copies;
+            }
+            Ok(total + copies)
+        })?;
+        stream.finish()?;
+        debug!("Total scratchcards: {total}");
+        Ok(total)
     }
 }
 
@@ -112,39 +651,243 @@ mod test {
     const P1_TRAIN_SOLUTION: usize = 13;
     const P2_TRAIN_SOLUTION: usize = 30;
 
-    #[test]
-    fn train_problem_1() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
-        let input = read_to_string("resources/day_04/train_problem_1.inp")?;
-        let result = solve(&input)?;
-        assert_eq!(result, P1_TRAIN_SOLUTION);
+    #[test_log::test]
+    fn validation() -> Result<(), Box<dyn Error>> {
+        use super::{validate, Issue, Side};
+        let input = read_to_string("resources/day_04/train_problem_2.inp")?;
+        assert_eq!(validate(&input)?, vec![]);
+
+        let input = read_to_string("resources/day_04/train_invalid.inp")?;
+        assert_eq!(
+            validate(&input)?,
+            vec![
+                Issue::DuplicateId {
+                    line: 3,
+                    id: 2,
+                    first_line: 2
+                },
+                Issue::UnexpectedId {
+                    line: 4,
+                    id: 4,
+                    expected: 3
+                },
+                Issue::UnexpectedId {
+                    line: 5,
+                    id: 6,
+                    expected: 5
+                },
+                Issue::DuplicateNumber {
+                    line: 5,
+                    side: Side::Winning,
+                    number: 28
+                },
+                Issue::UnexpectedCount {
+                    line: 6,
+                    side: Side::Mine,
+                    count: 9,
+                    expected: 8
+                },
+                Issue::WinsOverflow {
+                    line: 6,
+                    id: 7,
+                    wins: 1,
+                    missing: 1
+                },
+            ]
+        );
+        assert!(super::problem_2::solve(&input).is_err());
+        assert!(validate("Card 1: 1 2 | 3 x").is_err());
         Ok(())
     }
 
-    #[test]
-    fn solve_problem_1() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
-        let input = read_to_string("resources/day_04/problem_1.inp")?;
-        let result = solve(&input)?;
-        println!("{result}");
+    #[test_log::test]
+    fn train_cascade() -> Result<(), Box<dyn Error>> {
+        use super::problem_2::{Cascade, Step};
+        let input = read_to_string("resources/day_04/train_problem_2.inp")?;
+        let cascade = Cascade::new(&input)?;
+        assert_eq!(cascade.total(), P2_TRAIN_SOLUTION);
+        assert_eq!(cascade.copies(4), Some(8));
+        assert_eq!(cascade.copies(7), None);
+        assert_eq!(cascade.provenance(4), Some(&[(1, 1), (2, 2), (3, 4)][..]));
+        assert_eq!(cascade.provenance(1), Some(&[][..]));
+        assert_eq!(cascade.top_contributor(), Some((1, 14)));
+        assert_eq!(cascade.total_without(1), 15);
+        assert_eq!(cascade.total_without(6), 29);
+
+        let mut replay = cascade.replay();
+        let step = replay.next();
+        assert_eq!(
+            step,
+            Some(Step {
+                card: 1,
+                copies: 1,
+                won: 2..6
+            })
+        );
+        assert_eq!(replay.copies(), &[1, 2, 2, 2, 2, 1]);
+        assert_eq!(replay.last().map(|step| step.won), Some(7..7));
         Ok(())
     }
 
-    #[test]
-    fn train_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_2::solve;
-        let input = read_to_string("resources/day_04/train_problem_2.inp")?;
-        let result = solve(&input)?;
-        assert_eq!(result, P2_TRAIN_SOLUTION);
+    #[test_log::test]
+    fn streaming() -> Result<(), Box<dyn Error>> {
+        use super::{problem_1, problem_2, validate, Pile};
+        use std::{fs::File, io::BufReader};
+
+        let input = BufReader::new(File::open("resources/day_04/train_problem_1.inp")?);
+        assert_eq!(problem_1::solve_reader(input)?, P1_TRAIN_SOLUTION);
+        let input = BufReader::new(File::open("resources/day_04/train_problem_2.inp")?);
+        assert_eq!(problem_2::solve_reader(input)?, P2_TRAIN_SOLUTION);
+
+        let input = read_to_string("resources/day_04/train_invalid.inp")?;
+        let error = problem_2::solve_reader(input.as_bytes()).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Line 3: card 2 already appeared on line 2"
+        );
+        let lines: Vec<_> = input.lines().collect();
+        let input = [lines[0], lines[1], lines[5]].join("\n");
+        let error = problem_1::solve_reader(input.as_bytes()).unwrap_err();
+        assert_eq!(format!("{error:#}"), "Line 3: card 7 should be card 3");
+        // Wins past the end only stop part 2.
+        let input = input.replace("Card 7", "Card 3");
+        assert_eq!(problem_1::solve_reader(input.as_bytes())?, 11);
+        let error = problem_2::solve_reader(input.as_bytes()).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Line 1: card 1 wins 4 cards but 2 of them don't exist"
+        );
+        let input = input.replace(" 36 11 72", " 36 11");
+        assert_eq!(problem_1::solve_reader(input.as_bytes())?, 10);
+        let error = problem_2::solve_reader(input.as_bytes()).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Line 1: card 1 wins 4 cards but 2 of them don't exist"
+        );
+
+        // Repeated numbers and odd counts stop neither.
+        let input = read_to_string("resources/day_04/train_problem_2.inp")?
+            .replace("Card 6: 31 18", "Card 6: 31 31")
+            .replace(" 36 11\n", " 36 11 99\n");
+        assert_eq!(
+            problem_1::solve_reader(input.as_bytes())?,
+            P1_TRAIN_SOLUTION
+        );
+        assert_eq!(
+            problem_2::solve_reader(input.as_bytes())?,
+            P2_TRAIN_SOLUTION
+        );
+        assert_eq!(input.parse::<Pile>()?.solve_2()?, P2_TRAIN_SOLUTION);
+        assert_eq!(validate(&input)?.len(), 2);
+        Ok(())
+    }
+
+    #[test_log::test]
+    #[cfg_attr(
+        not(input_key),
+        ignore = "problem_2.inp is encrypted and there is no key"
+    )]
+    fn personal_input() -> Result<(), Box<dyn Error>> {
+        use super::{problem_2, validate};
+        use std::path::Path;
+
+        let input = crate::store::read(Path::new("resources/day_04/problem_2.inp"))?;
+        assert_eq!(validate(&input)?, vec![]);
+        assert_eq!(
+            problem_2::solve_reader(input.as_bytes())?,
+            problem_2::solve(&input)?
+        );
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn streaming_agrees_with_validate() -> Result<(), Box<dyn Error>> {
+        use super::{problem_2, validate, Issue, Side};
+
+        // The first card sets the counts, even when most cards disagree.
+        let odd_first = "Card 1: 1 2 3 | 4 5\nCard 2: 1 2 | 4 5\nCard 3: 6 7 | 8 9";
+        let invalid = read_to_string("resources/day_04/train_invalid.inp")?;
+        for input in [odd_first, &invalid] {
+            let error = problem_2::solve_reader(input.as_bytes()).err();
+            let breaking = validate(input)?.into_iter().find(Issue::breaks_copies);
+            assert_eq!(
+                error
+                    .as_ref()
+                    .and_then(|error| error.downcast_ref::<Issue>()),
+                breaking.as_ref(),
+                "{input}"
+            );
+        }
+        assert_eq!(
+            validate(odd_first)?[0],
+            Issue::UnexpectedCount {
+                line: 2,
+                side: Side::Winning,
+                count: 2,
+                expected: 3
+            }
+        );
         Ok(())
     }
 
-    #[test]
-    fn solve_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_2::solve;
-        let input = read_to_string("resources/day_04/problem_2.inp")?;
-        let result = solve(&input)?;
-        println!("{result}");
+    #[test_log::test]
+    fn checker_state_is_bounded() {
+        use super::{Card, Checker};
+
+        let mut checker = Checker::default();
+        for id in 1..=100_000 {
+            let card = Card {
+                id,
+                winning_numbers: vec![1, 2, 3],
+                numbers: vec![1, 2, 4],
+            };
+            assert_eq!(checker.card(&card, card.wins()), vec![]);
+            assert!(checker.reaching.len() <= 2);
+        }
+        assert!(checker.reaching.capacity() <= 8);
+        assert_eq!(checker.finish().len(), 2);
+    }
+
+    #[test_log::test]
+    fn pile() -> Result<(), Box<dyn Error>> {
+        use super::Pile;
+
+        let pile: Pile = read_to_string("resources/day_04/train_problem_1.inp")?.parse()?;
+        let card = &pile.cards()[0];
+        assert_eq!(card.id(), 1);
+        assert_eq!(card.winning_numbers(), &[41, 48, 83, 86, 17]);
+        assert_eq!(card.numbers().len(), 8);
+        assert_eq!((card.wins(), card.points()), (4, 8));
+        assert_eq!(pile.solve_1()?, P1_TRAIN_SOLUTION);
+        assert_eq!(pile.solve_2()?, P2_TRAIN_SOLUTION);
+        assert_eq!(pile.cascade()?.copies(5), Some(14));
+
+        let pile: Pile = read_to_string("resources/day_04/train_invalid.inp")?.parse()?;
+        assert_eq!(pile.validate().len(), 6);
+        assert!(pile.solve_1().is_err());
+
+        // Two cards short, which only leaves part 2 without an answer.
+        let input = read_to_string("resources/day_04/train_problem_1.inp")?;
+        let pile: Pile = input
+            .lines()
+            .take(4)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .parse()?;
+        assert_eq!(pile.solve_1()?, 8 + 2 + 2 + 1);
+        assert!(pile.solve_2().is_err());
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn display_round_trip() -> Result<(), Box<dyn Error>> {
+        use super::Pile;
+
+        for (path, input) in crate::resources::inputs(4)? {
+            let pile: Pile = input.parse()?;
+            assert_eq!(pile.to_string(), input.trim_end(), "{path:?}");
+            assert_eq!(pile.to_string().parse::<Pile>()?, pile);
+        }
         Ok(())
     }
 }
@@ -1,52 +1,86 @@
-use std::{num::ParseIntError, str::FromStr};
+//! Day 5: If You Give A Seed A Fertilizer. The model is an [`Almanac`] of
+//! seeds and the stages of [`Map`]s they go through.
 
-use anyhow::{bail, Context, Result};
+use std::{fmt, str::FromStr};
 
-struct Almanac {
+use anyhow::{bail, Context, Result};
+use log::{debug, trace};
+use nom::{
+    bytes::complete::{is_not, tag},
+    character::complete::{line_ending, space1},
+    sequence::{terminated, tuple},
+};
+
+use crate::parsers::{blank_line, label, lines, number, numbers, parse, sections, IResult};
+
+/// The seeds to plant and, for every stage, the ranges it remaps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Almanac {
     seeds: Vec<usize>,
+    // Name of every stage, such as seed-to-soil.
+    names: Vec<String>,
     maps: Vec<Vec<Map>>,
 }
 
+impl Almanac {
+    pub fn seeds(&self) -> &[usize] {
+        &self.seeds
+    }
+
+    /// Every stage's name in order, such as `seed-to-soil`.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Every stage in order, each with its ranges as listed.
+    pub fn maps(&self) -> &[Vec<Map>] {
+        &self.maps
+    }
+}
+
 impl FromStr for Almanac {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Almanac> {
-        let mut lines = s.lines();
-
-        let (_, seeds) = lines
-            .next()
-            .and_then(|line| line.split_once(":"))
-            .context("Parsing seeds line")?;
-        let seeds = seeds
-            .split_whitespace()
-            .map(|seed| seed.parse::<usize>())
-            .collect::<Result<Vec<usize>, ParseIntError>>()
-            .context("Parsing seeds line elements")?;
-
-        let mut map = Vec::new();
-        let mut maps = Vec::new();
-
-        if let Some(line) = lines.next() {
-            if !line.is_empty() {
-                bail!("Expected empty line after seeds");
-            }
-        }
+        let (seeds, _, stages) = parse(s, tuple((seeds, blank_line, sections(stage))))?;
+        let (names, maps): (Vec<_>, Vec<_>) = stages.into_iter().unzip();
 
-        while let Some(line) = lines.next() {
-            if line.is_empty() {
-                maps.push(map);
-                map = Vec::new();
-                continue;
-            }
+        debug!(
+            "Parsed almanac with {} seeds and {} maps",
+            seeds.len(),
+            maps.len()
+        );
 
-            if line.contains(':') {
-                continue;
-            }
+        Ok(Almanac { seeds, names, maps })
+    }
+}
 
-            map.push(line.parse()?);
-        }
+fn seeds(input: &str) -> IResult<'_, Vec<usize>> {
+    let (input, _) = label("seeds")(input)?;
+    numbers(input)
+}
 
-        Ok(Almanac { seeds, maps })
+// A stage's `name map:` line and the maps under it.
+fn stage(input: &str) -> IResult<'_, (String, Vec<Map>)> {
+    let (input, name) = terminated(is_not(" \r\n"), tuple((tag(" map:"), line_ending)))(input)?;
+    let (input, maps) = lines(map)(input)?;
+    Ok((input, (name.to_string(), maps)))
+}
+
+impl fmt::Display for Almanac {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "seeds:")?;
+        for seed in &self.seeds {
+            write!(f, " {seed}")?;
+        }
+        for (name, maps) in self.names.iter().zip(&self.maps) {
+            write!(f, "\n\n{name} map:")?;
+            for map in maps {
+                write!(f, "\n{map}")?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -74,47 +108,81 @@ impl TryFrom<Almanac> for Almanac2 {
     }
 }
 
-#[derive(Debug)]
-struct Map {
+/// A range of `size` values starting at `source` sent to `destination`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Map {
     source: usize,
     destination: usize,
     size: usize,
 }
 
+impl Map {
+    pub fn source(&self) -> usize {
+        self.source
+    }
+
+    pub fn destination(&self) -> usize {
+        self.destination
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.destination, self.source, self.size)
+    }
+}
+
 impl FromStr for Map {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Map> {
-        let mut map = s.split_whitespace();
-
-        let destination: usize = map.next().context("")?.parse().context("")?;
-        let source: usize = map.next().context("")?.parse().context("")?;
-        let size: usize = map.next().context("")?.parse().context("")?;
-
-        if map.next().is_some() {
-            anyhow::bail!("The row didn't contain 3 elements");
-        }
+        parse(s, map)
+    }
+}
 
-        Ok(Map {
+// The destination, source and size of a map, in that order.
+fn map(input: &str) -> IResult<'_, Map> {
+    let (input, (destination, _, source, _, size)) =
+        tuple((number, space1, number, space1, number))(input)?;
+    Ok((
+        input,
+        Map {
             source,
             destination,
             size,
-        })
-    }
+        },
+    ))
 }
 
-#[cfg(feature = "problem_1")]
-pub mod problem_1 {
+impl Almanac {
+    /// The lowest location any of the seeds ends up in.
+    pub fn solve_1(&self) -> Result<usize> {
+        lowest_location(self)
+    }
 
-    use super::Almanac;
-    use anyhow::{Context, Result};
+    /// The lowest location of any seed when seeds come as pairs of a start
+    /// and a length.
+    pub fn solve_2(&self) -> Result<usize> {
+        let almanac: Almanac2 = self.clone().try_into().context("Converting for almanac2")?;
+        lowest_range_location(almanac)
+    }
+}
 
-    pub(super) fn solve_almanac(almanac: Almanac) -> Result<usize> {
-        almanac
-            .seeds
-            .into_iter()
-            .map(|seed| {
-                almanac.maps.iter().fold(seed, |source, map| {
+fn lowest_location(almanac: &Almanac) -> Result<usize> {
+    let location = almanac
+        .seeds
+        .iter()
+        .map(|&seed| {
+            almanac
+                .maps
+                .iter()
+                .enumerate()
+                .fold(seed, |source, (stage, map)| {
                     let destin = map
                         .iter()
                         .find_map(|map| {
@@ -124,150 +192,184 @@ pub mod problem_1 {
                             None
                         })
                         .unwrap_or(source);
+                    trace!("Seed {seed} stage {stage}: {source} -> {destin}");
                     destin
                 })
-            })
-            .min()
-            .context("Finding minimum location")
-    }
-
-    pub fn solve(input: &str) -> Result<usize> {
-        let almanac: Almanac = input.parse()?;
-        solve_almanac(almanac)
-    }
+        })
+        .min()
+        .context("Finding minimum location")?;
+    debug!("Lowest location: {location}");
+    Ok(location)
 }
 
-#[cfg(feature = "problem_2")]
-pub mod problem_2 {
-
-    use super::{Almanac, Almanac2, Map};
-    use anyhow::{Context, Result};
-
-    fn next(source: (usize, usize), map: &Vec<Map>) -> Vec<(usize, usize)> {
-        let mut dest = vec![];
-        let mut current = source.0;
-        let end = source.1;
-        'sources: loop {
-            if current > end {
-                break;
-            }
+// The ranges `source` maps to through one stage.
+fn next(source: (usize, usize), map: &Vec<Map>) -> Vec<(usize, usize)> {
+    let mut dest = vec![];
+    let mut current = source.0;
+    let end = source.1;
+    'sources: loop {
+        if current > end {
+            break;
+        }
 
-            let mut min_start_range = None;
-            for map in map {
-                if current >= map.source {
-                    if current < map.source + map.size {
-                        let range_end = std::cmp::min(end, map.source + map.size - 1);
-                        dest.push((
-                            map.destination + (current - map.source),
-                            map.destination + (range_end - map.source),
-                        ));
-                        current = range_end + 1;
-                        continue 'sources;
-                    }
-                }
-
-                if map.source > current {
-                    min_start_range = match min_start_range {
-                        Some(value) => Some(std::cmp::min(value, map.source)),
-                        None => Some(map.source),
-                    };
-                }
+        let mut min_start_range = None;
+        for map in map {
+            if current >= map.source && current < map.source + map.size {
+                let range_end = std::cmp::min(end, map.source + map.size - 1);
+                dest.push((
+                    map.destination + (current - map.source),
+                    map.destination + (range_end - map.source),
+                ));
+                current = range_end + 1;
+                continue 'sources;
             }
 
-            if let Some(min_start) = min_start_range {
-                dest.push((current, min_start - 1));
-                current = min_start;
-            } else {
-                dest.push((current, end));
-                break;
+            if map.source > current {
+                min_start_range = match min_start_range {
+                    Some(value) => Some(std::cmp::min(value, map.source)),
+                    None => Some(map.source),
+                };
             }
         }
 
-        dest
+        // A gap that starts past the end of the range leaves the rest of it
+        // unmapped.
+        if let Some(min_start) = min_start_range.filter(|&start| start <= end) {
+            dest.push((current, min_start - 1));
+            current = min_start;
+        } else {
+            dest.push((current, end));
+            break;
+        }
     }
 
-    pub(super) fn solve_almanac(almanac: Almanac2) -> Result<usize> {
-        almanac
-            .seed_ranges
-            .into_iter()
-            .map(|(start, size)| {
-                almanac
-                    .maps
-                    .iter()
-                    .fold(vec![(start, start + size - 1)], |source, map| {
-                        source
-                            .into_iter()
-                            .map(|(start, end)| next((start, end), map))
-                            .flatten()
-                            .collect()
-                    })
-                    .into_iter()
-                    .map(|(f, _)| f)
-                    .min()
-            })
-            .flatten()
-            .min()
-            .context("Finding minimum location")
+    dest
+}
+
+fn lowest_range_location(almanac: Almanac2) -> Result<usize> {
+    let location = almanac
+        .seed_ranges
+        .into_iter()
+        .filter_map(|(start, size)| {
+            debug!("Mapping seed range {start}..{}", start + size);
+            almanac
+                .maps
+                .iter()
+                .enumerate()
+                .fold(vec![(start, start + size - 1)], |source, (stage, map)| {
+                    let dest: Vec<_> = source
+                        .into_iter()
+                        .flat_map(|(start, end)| next((start, end), map))
+                        .collect();
+                    debug!("Stage {stage} split into {} ranges", dest.len());
+                    dest
+                })
+                .into_iter()
+                .map(|(f, _)| f)
+                .min()
+        })
+        .min()
+        .context("Finding minimum location")?;
+    debug!("Lowest location: {location}");
+    Ok(location)
+}
+
+pub mod problem_1 {
+
+    use super::Almanac;
+    use anyhow::Result;
+
+    pub fn solve(input: &str) -> Result<usize> {
+        let almanac: Almanac = input.parse()?;
+        almanac.solve_1()
     }
+}
+
+pub mod problem_2 {
+
+    use super::Almanac;
+    use anyhow::{Context, Result};
 
     pub fn solve(input: &str) -> Result<usize> {
         let almanac: Almanac = input.parse().context("Parsing input")?;
-        let almanac: Almanac2 = almanac.try_into().context("Converting for almanac2")?;
-        solve_almanac(almanac)
+        almanac.solve_2()
     }
 }
 
 #[cfg(test)]
 mod test {
 
-    #[allow(unused_imports)]
     use std::error::Error;
-    #[allow(unused_imports)]
     use std::fs::read_to_string;
 
-    #[cfg(feature = "problem_1")]
-    const P1_TRAIN_SOLUTION: usize = 35;
+    use proptest::prelude::*;
 
-    #[cfg(feature = "problem_2")]
+    const P1_TRAIN_SOLUTION: usize = 35;
     const P2_TRAIN_SOLUTION: usize = 46;
 
-    #[test]
-    #[cfg(feature = "problem_1")]
-    fn train_problem_1() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
-        let input = read_to_string("resources/day_05/train_problem_1.inp")?;
-        let result = solve(&input)?;
-        assert_eq!(result, P1_TRAIN_SOLUTION);
+    #[test_log::test]
+    fn almanac() -> Result<(), Box<dyn Error>> {
+        use super::Almanac;
+
+        let almanac: Almanac = read_to_string("resources/day_05/train_problem_1.inp")?.parse()?;
+        assert_eq!(almanac.seeds(), &[79, 14, 55, 13]);
+        assert_eq!(almanac.maps().len(), 7);
+        assert_eq!(almanac.names()[6], "humidity-to-location");
+        let map = almanac.maps()[0][0];
+        assert_eq!((map.destination(), map.source(), map.size()), (50, 98, 2));
+        assert_eq!(almanac.solve_1()?, P1_TRAIN_SOLUTION);
+        assert_eq!(almanac.solve_2()?, P2_TRAIN_SOLUTION);
         Ok(())
     }
 
-    #[test]
-    #[cfg(feature = "problem_1")]
-    fn solve_problem_1() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
-        let input = read_to_string("resources/day_05/problem_1.inp")?;
-        let result = solve(&input)?;
-        println!("{result}");
+    #[test_log::test]
+    fn display_round_trip() -> Result<(), Box<dyn Error>> {
+        use super::Almanac;
+
+        for (path, input) in crate::resources::inputs(5)? {
+            let almanac: Almanac = input.parse()?;
+            assert_eq!(almanac.to_string(), input.trim_end(), "{path:?}");
+            assert_eq!(almanac.to_string().parse::<Almanac>()?, almanac);
+        }
         Ok(())
     }
 
-    #[test]
-    #[cfg(feature = "problem_2")]
-    fn train_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_2::solve;
-        let input = read_to_string("resources/day_05/train_problem_2.inp")?;
-        let result = solve(&input)?;
-        assert_eq!(result, P2_TRAIN_SOLUTION);
-        Ok(())
+    // Reference for part 2 that maps every single seed instead of splitting
+    // ranges.
+    fn brute_force_problem_2(input: &str) -> usize {
+        use super::{Almanac, Almanac2};
+
+        let almanac: Almanac = input.parse().unwrap();
+        let almanac: Almanac2 = almanac.try_into().unwrap();
+        almanac
+            .seed_ranges
+            .iter()
+            .flat_map(|&(start, size)| start..start + size)
+            .map(|seed| {
+                almanac.maps.iter().fold(seed, |source, map| {
+                    map.iter()
+                        .find(|map| source >= map.source && source < map.source + map.size)
+                        .map_or(source, |map| map.destination + (source - map.source))
+                })
+            })
+            .min()
+            .unwrap()
     }
 
-    #[test]
-    #[cfg(feature = "problem_2")]
-    fn solve_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_2::solve;
-        let input = read_to_string("resources/day_05/problem_2.inp")?;
-        let result = solve(&input)?;
-        println!("{result}");
-        Ok(())
+    proptest! {
+        #[test]
+        fn range_splitting_matches_brute_force(seed: u64, ranges in 1usize..8) {
+            use super::Almanac;
+            use crate::generators::{day_05::{generate, Params}, rng};
+
+            let params = Params {
+                seeds: 6,
+                ranges_per_map: ranges,
+                max_value: 200,
+            };
+            let input = generate(&mut rng(seed), &params);
+            let almanac: Almanac = input.parse().unwrap();
+            prop_assert_eq!(almanac.solve_2().unwrap(), brute_force_problem_2(&input));
+        }
     }
 }
@@ -1,46 +1,142 @@
-use std::str::FromStr;
-
-use anyhow::Context;
-
-#[derive(Debug)]
-struct Race {
+//! Day 6: Wait For It. The model is the [`Races`] sheet, or a single
+//! [`Race`] when the sheet is read ignoring the spaces between numbers.
+
+use std::{fmt, str::FromStr};
+
+use anyhow::{bail, Context};
+use log::{debug, trace};
+use nom::{
+    bytes::complete::tag,
+    character::complete::{char, digit1, line_ending, space1},
+    multi::many1,
+    sequence::{pair, separated_pair, tuple},
+};
+
+use crate::parsers::{parse, IResult};
+
+/// A race lasting `duration` ms whose best distance is `record` mm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Race {
     duration: usize,
     record: usize,
 }
 
-struct Races {
+impl Race {
+    pub fn new(duration: usize, record: usize) -> Race {
+        Race { duration, record }
+    }
+
+    pub fn duration(&self) -> usize {
+        self.duration
+    }
+
+    pub fn record(&self) -> usize {
+        self.record
+    }
+
+    /// How many whole ms holding times beat the record.
+    pub fn ways_to_win(&self) -> usize {
+        find_possible_solutions(*self)
+    }
+}
+
+/// Every race on the sheet.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Races {
     races: Vec<Race>,
+    // The column every number ends on, time row then distance row, so the
+    // sheet prints back as it was written. Empty unless read from a sheet.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    columns: [Vec<usize>; 2],
+}
+
+// Sheets with the same races are the same however they were spaced.
+impl PartialEq for Races {
+    fn eq(&self, other: &Self) -> bool {
+        self.races == other.races
+    }
+}
+
+impl Eq for Races {}
+
+impl Races {
+    pub fn races(&self) -> &[Race] {
+        &self.races
+    }
+
+    /// The product of the ways to win every race.
+    pub fn solve_1(&self) -> usize {
+        self.races.iter().map(Race::ways_to_win).product()
+    }
+}
+
+// Writes races as columns of the sheet, each number ending on its column of
+// `columns`. Without columns for every race, each is right aligned two spaces
+// past its longest number.
+fn write_sheet(
+    f: &mut fmt::Formatter<'_>,
+    races: &[Race],
+    columns: &[Vec<usize>; 2],
+) -> fmt::Result {
+    let aligned: Vec<_> = races
+        .iter()
+        .scan(9, |end, race| {
+            *end += race.duration.max(race.record).to_string().len() + 2;
+            Some(*end)
+        })
+        .collect();
+    let [times, distances] = match columns {
+        [times, distances] if times.len() == races.len() && distances.len() == races.len() => {
+            [times, distances]
+        }
+        _ => [&aligned, &aligned],
+    };
+    let durations: Vec<_> = races.iter().map(Race::duration).collect();
+    let records: Vec<_> = races.iter().map(Race::record).collect();
+    let rows = [
+        ("Time:", durations, times),
+        ("Distance:", records, distances),
+    ];
+    for (i, (label, values, ends)) in rows.into_iter().enumerate() {
+        if i > 0 {
+            writeln!(f)?;
+        }
+        write!(f, "{label}")?;
+        let mut at = label.len();
+        for (value, &end) in values.iter().zip(ends) {
+            let value = value.to_string();
+            let width = end.saturating_sub(at).max(value.len() + 1);
+            write!(f, "{value:>width$}")?;
+            at += width;
+        }
+    }
+    Ok(())
+}
+
+/// Writes a sheet with a single race.
+impl fmt::Display for Race {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_sheet(f, &[*self], &Default::default())
+    }
+}
+
+impl fmt::Display for Races {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_sheet(f, &self.races, &self.columns)
+    }
 }
 
 impl FromStr for Race {
     type Err = anyhow::Error;
 
+    /// Reads the whole sheet as one race, its numbers kerned together.
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let mut lines = s.lines();
-        let duration = lines
-            .next()
-            .context("Reading time")?
-            .split_once(':')
-            .context("Spliting time")?
-            .1
-            .chars()
-            .filter(|c| !c.is_whitespace())
-            .collect::<String>()
-            .parse::<usize>()
-            .context("Parsing time")?;
-
-        let record = lines
-            .next()
-            .context("Reading record")?
-            .split_once(':')
-            .context("Spliting record")?
-            .1
-            .chars()
-            .filter(|c| !c.is_whitespace())
-            .collect::<String>()
-            .parse::<usize>()
-            .context("Parsing record")?;
-
+        let (times, distances) = parse(s, sheet)?;
+        let duration = kerned(&times).context("Reading the time")?;
+        let record = kerned(&distances).context("Reading the distance")?;
+        debug!("Parsed race of {duration} ms with record {record} mm");
         Ok(Race { duration, record })
     }
 }
@@ -49,34 +145,63 @@ impl FromStr for Races {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let mut lines = s.lines();
-
-        let duration = lines
-            .next()
-            .context("Reading times")?
-            .split_once(':')
-            .context("Spliting times")?
-            .1
-            .split_whitespace()
-            .map(|time| time.parse::<usize>().unwrap());
-        let records = lines
-            .next()
-            .context("Reading records")?
-            .split_once(':')
-            .context("Spliting records")?
-            .1
-            .split_whitespace()
-            .map(|time| time.parse::<usize>().unwrap());
-
+        let (times, distances) = parse(s, sheet)?;
+        if times.len() != distances.len() {
+            bail!("{} times but {} distances", times.len(), distances.len());
+        }
+
+        let races = times
+            .iter()
+            .zip(&distances)
+            .map(|(&(duration, _), &(record, _))| {
+                Ok(Race::new(
+                    duration.parse().context("Reading a time")?,
+                    record.parse().context("Reading a distance")?,
+                ))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        debug!("Parsed {} races", races.len());
+
+        let ends = |row: &[(&str, usize)]| row.iter().map(|&(_, end)| end).collect();
         Ok(Races {
-            races: duration
-                .zip(records)
-                .map(|(duration, record)| Race { duration, record })
-                .collect(),
+            races,
+            columns: [ends(&times), ends(&distances)],
         })
     }
 }
 
+// The `Time:` and `Distance:` rows.
+fn sheet(input: &str) -> IResult<'_, (Row<'_>, Row<'_>)> {
+    separated_pair(row("Time"), line_ending, row("Distance"))(input)
+}
+
+// The digits of every number on a row, with the column each ends on.
+type Row<'a> = Vec<(&'a str, usize)>;
+
+fn row<'a>(name: &'static str) -> impl FnMut(&'a str) -> IResult<'a, Row<'a>> {
+    move |input| {
+        let (input, (_, _, numbers)) =
+            tuple((tag(name), char(':'), many1(pair(space1, digit1))))(input)?;
+        let mut end = name.len() + 1;
+        let numbers = numbers
+            .into_iter()
+            .map(|(spaces, digits): (&str, &str)| {
+                end += spaces.len() + digits.len();
+                (digits, end)
+            })
+            .collect();
+        Ok((input, numbers))
+    }
+}
+
+// A row's numbers as a single one, ignoring the spaces between them.
+fn kerned(row: &[(&str, usize)]) -> anyhow::Result<usize> {
+    let digits: String = row.iter().map(|&(digits, _)| digits).collect();
+    digits
+        .parse()
+        .with_context(|| format!("Invalid number {digits:?}"))
+}
+
 fn find_possible_solutions(race: Race) -> usize {
     // distance(h, d) = h*(d-h) = h^2 - hd - r
     //     -d +/- sqrt(d^2 -4*1*r)
@@ -86,6 +211,11 @@ fn find_possible_solutions(race: Race) -> usize {
     let r = race.record as f64;
 
     let sq: f64 = d * d - 4f64 * r;
+    // Without two distinct roots even the best holding time only ties.
+    if sq <= 0f64 {
+        trace!("{race:?} can't be won");
+        return 0;
+    }
     let sq = sq.sqrt();
 
     let lower: f64 = (d - sq) / 2f64;
@@ -93,11 +223,15 @@ fn find_possible_solutions(race: Race) -> usize {
 
     let lower = (lower + 1f64).floor() as usize;
     let upper = (upper - 1f64).ceil() as usize;
+    if upper < lower {
+        trace!("{race:?} has no whole holding time that wins");
+        return 0;
+    }
+    trace!("{race:?} is won holding between {lower} and {upper} ms");
 
     1 + (upper - lower)
 }
 
-#[cfg(feature = "problem_1")]
 pub mod problem_1 {
 
     use super::Races;
@@ -105,18 +239,10 @@ pub mod problem_1 {
 
     pub fn solve(input: &str) -> Result<usize> {
         let races: Races = input.parse().context("Parsing input")?;
-
-        let prod = races
-            .races
-            .into_iter()
-            .map(super::find_possible_solutions)
-            .product();
-
-        Ok(prod)
+        Ok(races.solve_1())
     }
 }
 
-#[cfg(feature = "problem_2")]
 pub mod problem_2 {
 
     use super::Race;
@@ -124,64 +250,89 @@ pub mod problem_2 {
 
     pub fn solve(input: &str) -> Result<usize> {
         let race: Race = input.parse().context("Parsing input")?;
-
-        let result = super::find_possible_solutions(race);
-
-        Ok(result)
+        Ok(race.ways_to_win())
     }
 }
 
 #[cfg(test)]
 mod test {
 
-    #[allow(unused_imports)]
     use std::error::Error;
-    #[allow(unused_imports)]
     use std::fs::read_to_string;
 
-    #[cfg(feature = "problem_1")]
-    const P1_TRAIN_SOLUTION: usize = 288;
+    use proptest::prelude::*;
+
+    use super::{find_possible_solutions, Race};
 
-    #[cfg(feature = "problem_2")]
+    const P1_TRAIN_SOLUTION: usize = 288;
     const P2_TRAIN_SOLUTION: usize = 71503;
 
-    #[test]
-    #[cfg(feature = "problem_1")]
-    fn train_problem_1() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
+    #[test_log::test]
+    fn races() -> Result<(), Box<dyn Error>> {
+        use super::Races;
+
         let input = read_to_string("resources/day_06/train_problem_1.inp")?;
-        let result = solve(&input)?;
-        assert_eq!(result, P1_TRAIN_SOLUTION);
+        let races: Races = input.parse()?;
+        let ways: Vec<_> = races.races().iter().map(Race::ways_to_win).collect();
+        assert_eq!(ways, [4, 8, 9]);
+        assert_eq!(Race::new(4, 4).ways_to_win(), 0);
+        assert_eq!(Race::new(2, 5).ways_to_win(), 0);
+        assert_eq!(races.solve_1(), P1_TRAIN_SOLUTION);
+
+        let spaced: Races = "Time: 7 15 30\nDistance: 9 40 200".parse()?;
+        assert_eq!(spaced, races);
+        assert_ne!(spaced.to_string(), races.to_string());
+
+        let race: Race = input.parse()?;
+        assert_eq!(race, Race::new(71530, 940200));
+        assert_eq!(race.ways_to_win(), P2_TRAIN_SOLUTION);
         Ok(())
     }
 
-    #[test]
-    #[cfg(feature = "problem_1")]
-    fn solve_problem_1() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
-        let input = read_to_string("resources/day_06/problem_1.inp")?;
-        let result = solve(&input)?;
-        println!("{result}");
-        Ok(())
-    }
+    #[test_log::test]
+    fn display_round_trip() -> Result<(), Box<dyn Error>> {
+        use super::Races;
+
+        for (path, input) in crate::resources::inputs(6)? {
+            let races: Races = input.parse()?;
+            assert_eq!(races.to_string(), input.trim_end(), "{path:?}");
+            assert_eq!(races.to_string().parse::<Races>()?, races);
+            let race: Race = input.parse()?;
+            assert_eq!(race.to_string().parse::<Race>()?, race, "{path:?}");
+        }
 
-    #[test]
-    #[cfg(feature = "problem_2")]
-    fn train_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_2::solve;
+        // A kerned race only keeps its numbers, so it prints as a sheet of
+        // its own.
         let input = read_to_string("resources/day_06/train_problem_1.inp")?;
-        let result = solve(&input)?;
-        assert_eq!(result, P2_TRAIN_SOLUTION);
+        assert_eq!(
+            input.parse::<Race>()?.to_string(),
+            "Time:       71530\nDistance:  940200"
+        );
         Ok(())
     }
 
-    #[test]
-    #[cfg(feature = "problem_2")]
-    fn solve_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_2::solve;
-        let input = read_to_string("resources/day_06/problem_1.inp")?;
-        let result = solve(&input)?;
-        println!("{result}");
-        Ok(())
+    // Reference that simulates every possible holding time.
+    fn brute_force(race: &Race) -> usize {
+        (0..=race.duration)
+            .filter(|hold| hold * (race.duration - hold) > race.record)
+            .count()
+    }
+
+    proptest! {
+        #[test]
+        fn quadratic_matches_brute_force(
+            duration in 0usize..10_000,
+            record in any::<prop::sample::Index>(),
+        ) {
+            // Records up to a little past the best distance, so races that
+            // can't be won come up too.
+            let best = (duration / 2) * (duration - duration / 2);
+            let race = Race {
+                duration,
+                record: record.index(best + duration + 1),
+            };
+            let expected = brute_force(&race);
+            prop_assert_eq!(find_possible_solutions(race), expected);
+        }
     }
 }
@@ -1,43 +1,79 @@
-use anyhow::{bail, Context};
+//! Day 7: Camel Cards. The model is a [`Game`] of [`Hand`]s, generic over
+//! whether jacks are plain cards, [`Card1`], or jokers, [`Card2`].
+
+use anyhow::Context;
+use log::{debug, trace};
+use nom::{
+    character::complete::{anychar, space1},
+    sequence::{separated_pair, tuple},
+};
 use std::{
-    cmp::{Ordering, PartialOrd},
-    collections::HashMap,
-    hash::Hash,
-    ops::Deref,
+    cmp::Ordering,
+    fmt::{self, Debug},
+    io::BufRead,
     str::FromStr,
 };
 
-trait Card: Ord + PartialEq + Eq + Hash {
-    fn from_char(c: char) -> Self;
+use crate::parsers::{convert, lines, number, parse, IResult};
+use crate::streaming::try_fold_lines;
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for super::Card1 {}
+    impl Sealed for super::Card2 {}
+}
+
+/// The rules a card follows. Only [`Card1`] and [`Card2`] implement it.
+pub trait Card: sealed::Sealed + Debug + Copy {
+    fn from_char(c: char) -> Option<Self>;
+    fn symbol(&self) -> char;
+    /// From 0 for the weakest card up to 12 for the strongest.
+    fn strength(&self) -> u8;
     fn is_wildcard(&self) -> bool;
 }
 
-#[derive(Debug, Ord, PartialEq, Eq, Hash)]
-struct Card1(char);
+/// A card where jacks rank between queens and tens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "char", into = "char")
+)]
+pub struct Card1(char);
 
-impl Deref for Card1 {
-    type Target = char;
-    fn deref(&self) -> &Self::Target {
-        &self.0
+impl TryFrom<char> for Card1 {
+    type Error = anyhow::Error;
+
+    fn try_from(value: char) -> anyhow::Result<Self> {
+        Self::from_char(value).with_context(|| format!("Unknown card {value}"))
     }
 }
 
-impl PartialOrd for Card1 {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        const HAND_STRING_ORD: [char; 13] = [
-            'A', 'K', 'Q', 'J', 'T', '9', '8', '7', '6', '5', '4', '3', '2',
-        ];
-
-        let s = HAND_STRING_ORD.iter().position(|&c| c == **self).unwrap();
-        let o = HAND_STRING_ORD.iter().position(|&c| c == **other).unwrap();
-
-        s.partial_cmp(&o)
+impl From<Card1> for char {
+    fn from(card: Card1) -> char {
+        card.0
     }
 }
 
 impl Card for Card1 {
-    fn from_char(c: char) -> Self {
-        Self(c)
+    fn from_char(c: char) -> Option<Self> {
+        matches!(c, '2'..='9' | 'T' | 'J' | 'Q' | 'K' | 'A').then_some(Self(c))
+    }
+
+    fn symbol(&self) -> char {
+        self.0
+    }
+
+    fn strength(&self) -> u8 {
+        match self.0 {
+            'A' => 12,
+            'K' => 11,
+            'Q' => 10,
+            'J' => 9,
+            'T' => 8,
+            c => c as u8 - b'2',
+        }
     }
 
     fn is_wildcard(&self) -> bool {
@@ -45,42 +81,59 @@ impl Card for Card1 {
     }
 }
 
-#[derive(Debug, Ord, PartialEq, Eq, Hash)]
-struct Card2(char);
+/// A card where jacks are jokers, the weakest card but wild.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "char", into = "char")
+)]
+pub struct Card2(char);
+
+impl TryFrom<char> for Card2 {
+    type Error = anyhow::Error;
 
-impl Deref for Card2 {
-    type Target = char;
-    fn deref(&self) -> &Self::Target {
-        &self.0
+    fn try_from(value: char) -> anyhow::Result<Self> {
+        Self::from_char(value).with_context(|| format!("Unknown card {value}"))
     }
 }
 
-impl PartialOrd for Card2 {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        const HAND_STRING_ORD: [char; 13] = [
-            'A', 'K', 'Q', 'T', '9', '8', '7', '6', '5', '4', '3', '2', 'J',
-        ];
-
-        let s = HAND_STRING_ORD.iter().position(|&c| c == **self).unwrap();
-        let o = HAND_STRING_ORD.iter().position(|&c| c == **other).unwrap();
-
-        s.partial_cmp(&o)
+impl From<Card2> for char {
+    fn from(card: Card2) -> char {
+        card.0
     }
 }
 
 impl Card for Card2 {
-    fn from_char(c: char) -> Self {
-        Self(c)
+    fn from_char(c: char) -> Option<Self> {
+        Card1::from_char(c).map(|card| Self(card.0))
+    }
+
+    fn symbol(&self) -> char {
+        self.0
+    }
+
+    fn strength(&self) -> u8 {
+        match self.0 {
+            'J' => 0,
+            'A' => 12,
+            'K' => 11,
+            'Q' => 10,
+            'T' => 9,
+            c => c as u8 - b'1',
+        }
     }
 
     fn is_wildcard(&self) -> bool {
-        **self == 'J'
+        self.0 == 'J'
     }
 }
 
-#[derive(Debug, Ord, PartialEq, Eq)]
-struct Hand<C: Card> {
-    cards: Vec<C>,
+/// Five cards and what was bid on them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hand<C: Card> {
+    cards: [C; 5],
     bid: usize,
 }
 
@@ -91,201 +144,521 @@ where
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> anyhow::Result<Self> {
-        let (cards, bid) = s.split_once(' ').context("Spliting round")?;
+        parse(s, Hand::parse)
+    }
+}
 
-        if cards.len() != 5 {
-            bail!("Hand ought to have 5 cards")
-        }
+impl<C: Card> fmt::Display for Hand<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.symbols(), self.bid)
+    }
+}
 
-        let cards: Vec<C> = cards.chars().map(|c| C::from_char(c)).collect();
-        let bid: usize = bid.parse()?;
+impl<C: Card> Hand<C> {
+    // The five cards and the bid, separated by spaces.
+    fn parse(input: &str) -> IResult<'_, Self> {
+        let (input, (cards, bid)) = separated_pair(Hand::cards_of, space1, number)(input)?;
+        Ok((input, Self { cards, bid }))
+    }
 
-        Ok(Self { cards, bid })
+    fn cards_of(input: &str) -> IResult<'_, [C; 5]> {
+        let card = || {
+            convert(anychar, |c| {
+                C::from_char(c).with_context(|| format!("Unknown card {c}"))
+            })
+        };
+        let (input, (a, b, c, d, e)) = tuple((card(), card(), card(), card(), card()))(input)?;
+        Ok((input, [a, b, c, d, e]))
     }
-}
 
-impl<C> PartialOrd for Hand<C>
-where
-    C: PartialOrd + PartialEq + Card,
-{
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        let self_type: HandType = self.try_into().ok()?;
-        let other_type: HandType = other.try_into().ok()?;
+    // A bare hand, as when explaining it outside of a game.
+    fn unbid(cards: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            cards: parse(cards, Hand::cards_of)?,
+            bid: 0,
+        })
+    }
+
+    pub fn cards(&self) -> &[C; 5] {
+        &self.cards
+    }
+
+    pub fn bid(&self) -> usize {
+        self.bid
+    }
+
+    pub fn hand_type(&self) -> HandType {
+        self.into()
+    }
 
-        Some(
-            self_type
-                .partial_cmp(&other_type)?
-                .then(self.cards.partial_cmp(&other.cards)?),
-        )
+    fn symbols(&self) -> String {
+        self.cards.iter().map(|card| card.symbol()).collect()
+    }
+
+    pub fn explain(&self) -> Explanation {
+        let wildcards = self.cards.iter().filter(|card| card.is_wildcard()).count();
+        // Wildcards join the largest group, the strongest one among ties, or
+        // become aces when there is nothing else.
+        let joined = self
+            .cards
+            .iter()
+            .filter(|card| !card.is_wildcard())
+            .max_by_key(|card| {
+                let size = self
+                    .cards
+                    .iter()
+                    .filter(|other| other.symbol() == card.symbol())
+                    .count();
+                (size, card.strength())
+            })
+            .map_or('A', |card| card.symbol());
+
+        Explanation {
+            cards: self.symbols(),
+            hand_type: self.into(),
+            wildcards: (wildcards > 0).then_some((wildcards, joined)),
+        }
+    }
+
+    pub fn compare(&self, other: &Self) -> Comparison {
+        let (first, second) = (self.explain(), other.explain());
+        let reason = match first.hand_type.cmp(&second.hand_type) {
+            Ordering::Equal => (0..5)
+                .find(|&i| self.cards[i].strength() != other.cards[i].strength())
+                .map_or(Reason::Tie, |position| Reason::Card { position }),
+            _ => Reason::Type,
+        };
+
+        Comparison {
+            ordering: self.key().cmp(&other.key()),
+            first,
+            second,
+            reason,
+        }
+    }
+
+    /// Packs the hand type and then every card's strength, 4 bits each, so
+    /// that stronger hands get larger keys.
+    fn key(&self) -> u32 {
+        let hand_type = HandType::from(self) as u32;
+        self.cards
+            .iter()
+            .fold(hand_type, |key, card| key << 4 | card.strength() as u32)
     }
 }
 
-#[derive(Debug, PartialOrd, Ord, PartialEq, Eq)]
-enum HandType {
-    Five,
-    Four,
-    Full,
-    Three,
-    Two,
-    One,
+// Weakest first, so the discriminant can lead the sort key.
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
+pub enum HandType {
     High,
+    One,
+    Two,
+    Three,
+    Full,
+    Four,
+    Five,
 }
 
-impl<C: Card> TryFrom<&Hand<C>> for HandType {
-    type Error = anyhow::Error;
-
-    fn try_from(value: &Hand<C>) -> anyhow::Result<Self> {
+impl<C: Card> From<&Hand<C>> for HandType {
+    fn from(value: &Hand<C>) -> Self {
         use HandType::*;
 
-        let groups = value.cards.iter().fold(HashMap::new(), |mut groups, card| {
-            *groups.entry(card).or_insert(0usize) += 1;
-            groups
-        });
+        let mut groups = [0u8; 13];
+        let mut wildcards = 0;
+        for card in &value.cards {
+            if card.is_wildcard() {
+                wildcards += 1;
+            } else {
+                groups[card.strength() as usize] += 1;
+            }
+        }
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+        // Wildcards always join the largest group.
+        groups[0] += wildcards;
+
+        trace!(
+            "Card groups {:?} include {wildcards} wildcards",
+            &groups[..2]
+        );
+        match groups[..2] {
+            [5, _] => Five,
+            [4, _] => Four,
+            [3, 2] => Full,
+            [3, _] => Three,
+            [2, 2] => Two,
+            [2, _] => One,
+            _ => High,
+        }
+    }
+}
 
-        let mut groups = groups.iter().collect::<Vec<_>>();
-        let wildcards: usize = groups
-            .iter()
-            .find_map(|(card, reps)| card.is_wildcard().then_some(**reps))
-            .unwrap_or(0usize);
+impl fmt::Display for HandType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HandType::High => "high card",
+            HandType::One => "one pair",
+            HandType::Two => "two pair",
+            HandType::Three => "three of a kind",
+            HandType::Full => "full house",
+            HandType::Four => "four of a kind",
+            HandType::Five => "five of a kind",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Why a hand has the type it has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub cards: String,
+    pub hand_type: HandType,
+    /// How many wildcards the hand holds and which card they stood in for.
+    pub wildcards: Option<(usize, char)>,
+}
 
-        if wildcards == 5usize {
-            return Ok(Five);
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is {}", self.cards, self.hand_type)?;
+        if let Some((count, card)) = self.wildcards {
+            write!(f, " with {count} J as {card}")?;
         }
+        Ok(())
+    }
+}
 
-        groups.retain(|&card| !card.0.is_wildcard());
-        groups.sort_by(|a, b| b.1.cmp(a.1).then(b.0.cmp(a.0)));
-        let mut groups: Vec<usize> = groups.iter().map(|(_, reps)| **reps).collect();
-        if let Some(largest) = groups.get_mut(0) {
-            *largest += wildcards;
-        } else {
-            bail!("adding wildcard");
+/// What decided the order between two hands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    Type,
+    /// First position, from 0, where the cards differ.
+    Card {
+        position: usize,
+    },
+    Tie,
+}
+
+/// How the first hand ranks against the second one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    pub first: Explanation,
+    pub second: Explanation,
+    pub ordering: Ordering,
+    pub reason: Reason,
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (winner, loser) = match self.ordering {
+            Ordering::Less => (&self.second, &self.first),
+            _ => (&self.first, &self.second),
+        };
+        match self.reason {
+            Reason::Tie => write!(f, "{} ties with {}", self.first.cards, self.second.cards),
+            Reason::Type => write!(
+                f,
+                "{} outranks {} because {} beats {}",
+                winner.cards, loser.cards, winner.hand_type, loser.hand_type
+            ),
+            Reason::Card { position } => write!(
+                f,
+                "{} outranks {}, both {}, because card {} is {} against {}",
+                winner.cards,
+                loser.cards,
+                winner.hand_type,
+                position + 1,
+                winner.cards.chars().nth(position).unwrap(),
+                loser.cards.chars().nth(position).unwrap()
+            ),
         }
+    }
+}
+
+/// Every hand dealt, in the order they were listed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Game<C: Card> {
+    hands: Vec<Hand<C>>,
+}
+
+impl<C: Card> FromStr for Game<C> {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let hands = parse(s, lines(Hand::parse)).context("Parsing hands")?;
+        Ok(Game { hands })
+    }
+}
 
-        let mut hand_type = None;
-
-        for reps in groups {
-            match reps {
-                5 => hand_type = Some(Five),
-                4 => hand_type = Some(Four),
-                3 => hand_type = Some(Three),
-                2 => {
-                    hand_type = match hand_type {
-                        Some(Three) => Some(Full),
-                        Some(One) => Some(Two),
-                        None => Some(One),
-                        _ => unreachable!("Others are guarded by hand max size (5)"),
-                    }
-                }
-                1 => {
-                    hand_type = match hand_type {
-                        None => Some(High),
-                        _ => break,
-                    }
-                }
-                _ => unreachable!("No more than 5 cards should be counted"),
+impl<C: Card> fmt::Display for Game<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hands: Vec<_> = self.hands.iter().map(Hand::to_string).collect();
+        write!(f, "{}", hands.join("\n"))
+    }
+}
+
+impl<C: Card> Game<C> {
+    pub fn hands(&self) -> &[Hand<C>] {
+        &self.hands
+    }
+
+    /// The hands from weakest to strongest.
+    pub fn ranked(&self) -> Vec<&Hand<C>> {
+        let mut hands: Vec<_> = self.hands.iter().collect();
+        hands.sort_by_cached_key(|hand| hand.key());
+        hands
+    }
+
+    /// The sum of every bid times the rank of its hand.
+    pub fn winnings(&self) -> usize {
+        self.ranked()
+            .into_iter()
+            .enumerate()
+            .fold(0, |sum, (i, hand)| sum + (i + 1) * hand.bid)
+    }
+
+    /// Every hand from weakest to strongest, each with what put it above the
+    /// previous one. Each rank gets its own line, starting below the runner's
+    /// label.
+    pub fn report(&self) -> String {
+        let hands = self.ranked();
+        let mut report = String::new();
+        for (i, hand) in hands.iter().enumerate() {
+            report.push_str(&format!("\nRank {}: {}", i + 1, hand.explain()));
+            if let Some(previous) = i.checked_sub(1).map(|previous| hands[previous]) {
+                report.push_str(&format!("; {}", hand.compare(previous)));
             }
         }
-
-        hand_type.context("No cards found")
+        report
     }
 }
 
-fn solve<C: Card>(hands: Vec<Hand<C>>) -> anyhow::Result<usize> {
-    let mut game = hands;
-    game.sort();
+fn report<C: Card>(input: &str) -> anyhow::Result<String> {
+    Ok(input.parse::<Game<C>>()?.report())
+}
+
+#[aoc(day7, part1, Explain)]
+pub fn explain_1(input: &str) -> anyhow::Result<String> {
+    report::<Card1>(input)
+}
+
+#[aoc(day7, part2, Explain)]
+pub fn explain_2(input: &str) -> anyhow::Result<String> {
+    report::<Card2>(input)
+}
+
+// Only the key and bid of each hand are kept, so ranking a stream of hands
+// holds a few bytes per hand rather than its text.
+fn solve<C: Card, R: BufRead>(reader: R) -> anyhow::Result<usize> {
+    let mut game: Vec<(u32, usize)> = try_fold_lines(reader, vec![], |mut game, line| {
+        let hand = line.parse::<Hand<C>>().context("Parsing hand")?;
+        // Scoring each hand once up front keeps the sort to plain integers.
+        let key = hand.key();
+        trace!("{hand:?} has key {key:#07x}");
+        game.push((key, hand.bid));
+        Ok(game)
+    })?;
+    debug!("Ranking {} hands", game.len());
+    game.sort_unstable();
 
     let sum = game
         .into_iter()
-        .rev()
         .enumerate()
-        .fold(0, |sum, (i, hand)| sum + (i + 1) * hand.bid);
+        .fold(0, |sum, (i, (_, bid))| sum + (i + 1) * bid);
 
+    debug!("Total winnings: {sum}");
     Ok(sum)
 }
 
 pub mod problem_1 {
 
-    use super::{Card1, Hand};
-    use anyhow::{Context, Result};
+    use std::io::BufRead;
+
+    use super::{Card1, Comparison, Explanation, Hand};
+    use anyhow::Result;
+
+    pub fn explain(hand: &str) -> Result<Explanation> {
+        Ok(Hand::<Card1>::unbid(hand)?.explain())
+    }
+
+    pub fn compare(first: &str, second: &str) -> Result<Comparison> {
+        Ok(Hand::<Card1>::unbid(first)?.compare(&Hand::unbid(second)?))
+    }
 
     pub fn solve(input: &str) -> Result<usize> {
-        let game = input
-            .lines()
-            .map(|hand| hand.parse::<Hand<Card1>>())
-            .collect::<Result<Vec<_>>>()
-            .context("Parsing hands")?;
+        solve_reader(input.as_bytes())
+    }
 
-        super::solve(game)
+    pub fn solve_reader<R: BufRead>(reader: R) -> Result<usize> {
+        super::solve::<Card1, _>(reader)
     }
 }
 
-#[cfg(feature = "problem_2")]
 pub mod problem_2 {
 
-    use super::{Card2, Hand};
-    use anyhow::{Context, Result};
+    use std::io::BufRead;
+
+    use super::{Card2, Comparison, Explanation, Hand};
+    use anyhow::Result;
+
+    pub fn explain(hand: &str) -> Result<Explanation> {
+        Ok(Hand::<Card2>::unbid(hand)?.explain())
+    }
+
+    pub fn compare(first: &str, second: &str) -> Result<Comparison> {
+        Ok(Hand::<Card2>::unbid(first)?.compare(&Hand::unbid(second)?))
+    }
 
     pub fn solve(input: &str) -> Result<usize> {
-        let game = input
-            .lines()
-            .map(|hand| hand.parse::<Hand<Card2>>())
-            .collect::<Result<Vec<_>>>()
-            .context("Parsing hands")?;
+        solve_reader(input.as_bytes())
+    }
 
-        super::solve(game)
+    pub fn solve_reader<R: BufRead>(reader: R) -> Result<usize> {
+        super::solve::<Card2, _>(reader)
     }
 }
 
 #[cfg(test)]
 mod test {
 
-    #[allow(unused_imports)]
     use std::error::Error;
-    #[allow(unused_imports)]
     use std::fs::read_to_string;
-    #[cfg(feature = "problem_1")]
-    const P1_TRAIN_SOLUTION: usize = 6440;
-
-    #[cfg(feature = "problem_2")]
-    const P2_TRAIN_SOLUTION: usize = 5905;
 
-    #[test]
-    #[cfg(feature = "problem_1")]
-    fn train_problem_1() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
+    use proptest::prelude::*;
+
+    #[test_log::test]
+    fn hand_keys() -> Result<(), Box<dyn Error>> {
+        use super::{Card1, Card2, Hand, HandType};
+
+        let types = |input: &str| -> Result<Vec<HandType>, Box<dyn Error>> {
+            Ok(input
+                .lines()
+                .map(|hand| hand.parse::<Hand<Card1>>())
+                .collect::<anyhow::Result<Vec<_>>>()?
+                .iter()
+                .map(HandType::from)
+                .collect())
+        };
         let input = read_to_string("resources/day_07/train_problem_1.inp")?;
-        let result = solve(&input)?;
-        assert_eq!(result, P1_TRAIN_SOLUTION);
+        use HandType::*;
+        assert_eq!(types(&input)?, vec![One, Three, Two, Two, Three]);
+
+        let key = |hand: &str| hand.parse::<Hand<Card1>>().map(|hand| hand.key());
+        assert!(key("KK677 1")? > key("KTJJT 1")?);
+        assert!(key("33332 1")? > key("2AAAA 1")?);
+        assert!(key("AAAAA 1")? > key("AAAAK 1")?);
+        let key = |hand: &str| hand.parse::<Hand<Card2>>().map(|hand| hand.key());
+        assert!(key("KTJJT 1")? > key("QQQJA 1")?);
+        assert!(key("JKKK2 1")? < key("QQQQ2 1")?);
+
+        assert!("AAAA 1".parse::<Hand<Card1>>().is_err());
+        assert!("AAAA1 1".parse::<Hand<Card1>>().is_err());
         Ok(())
     }
 
-    #[test]
-    #[cfg(feature = "problem_1")]
-    fn solve_problem_1() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
-        let input = read_to_string("resources/day_07/problem_1.inp")?;
-        let result = solve(&input)?;
-        println!("{result}");
+    #[test_log::test]
+    fn explain_hands() -> Result<(), Box<dyn Error>> {
+        use super::{problem_1, problem_2, HandType, Reason};
+        use std::cmp::Ordering;
+
+        let explanation = problem_2::explain("KTJJT")?;
+        assert_eq!(explanation.hand_type, HandType::Four);
+        assert_eq!(explanation.wildcards, Some((2, 'T')));
+        assert_eq!(
+            explanation.to_string(),
+            "KTJJT is four of a kind with 2 J as T"
+        );
+        assert_eq!(problem_2::explain("JJJJJ")?.wildcards, Some((5, 'A')));
+        assert_eq!(problem_1::explain("KTJJT")?.wildcards, None);
+
+        let comparison = problem_1::compare("KTJJT", "KK677")?;
+        assert_eq!(comparison.ordering, Ordering::Less);
+        assert_eq!(comparison.reason, Reason::Card { position: 1 });
+        assert_eq!(
+            comparison.to_string(),
+            "KK677 outranks KTJJT, both two pair, because card 2 is K against T"
+        );
+        let comparison = problem_2::compare("KTJJT", "KK677")?;
+        assert_eq!(comparison.ordering, Ordering::Greater);
+        assert_eq!(comparison.reason, Reason::Type);
+        assert_eq!(
+            comparison.to_string(),
+            "KTJJT outranks KK677 because four of a kind beats two pair"
+        );
+        assert_eq!(problem_1::compare("T55J5", "T55J5")?.reason, Reason::Tie);
+        assert!(problem_1::explain("T55J").is_err());
+
+        let input = read_to_string("resources/day_07/train_problem_2.inp")?;
+        let report = super::explain_2(&input)?;
+        let mut ranks = report.lines().skip(1);
+        assert_eq!(ranks.next(), Some("Rank 1: 32T3K is one pair"));
+        assert_eq!(
+            ranks.last(),
+            Some("Rank 5: KTJJT is four of a kind with 2 J as T; KTJJT outranks QQQJA, both four of a kind, because card 1 is K against Q")
+        );
         Ok(())
     }
 
-    #[test]
-    #[cfg(feature = "problem_2")]
-    fn train_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_2::solve;
-        let input = read_to_string("resources/day_07/train_problem_2.inp")?;
-        let result = solve(&input)?;
-        assert_eq!(result, P2_TRAIN_SOLUTION);
+    #[test_log::test]
+    fn game() -> Result<(), Box<dyn Error>> {
+        use super::{Card, Card1, Card2, Game, HandType};
+
+        let input = read_to_string("resources/day_07/train_problem_1.inp")?;
+        let game: Game<Card1> = input.parse()?;
+        let hand = &game.hands()[1];
+        let cards: String = hand.cards().iter().map(Card::symbol).collect();
+        assert_eq!((cards.as_str(), hand.bid()), ("T55J5", 684));
+        assert_eq!(hand.hand_type(), HandType::Three);
+        assert_eq!(game.ranked()[0].bid(), 765);
+        assert_eq!(game.winnings(), 6440);
+        assert_eq!(input.parse::<Game<Card2>>()?.winnings(), 5905);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn display_round_trip() -> Result<(), Box<dyn Error>> {
+        use super::{Card1, Card2, Game};
+
+        for (path, input) in crate::resources::inputs(7)? {
+            let game: Game<Card1> = input.parse()?;
+            assert_eq!(game.to_string(), input.trim_end(), "{path:?}");
+            assert_eq!(game.to_string().parse::<Game<Card1>>()?, game);
+            let game: Game<Card2> = input.parse()?;
+            assert_eq!(game.to_string().parse::<Game<Card2>>()?, game);
+        }
         Ok(())
     }
 
-    #[test]
-    #[cfg(feature = "problem_2")]
-    fn solve_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_2::solve;
-        let input = read_to_string("resources/day_07/problem_2.inp")?;
-        let result = solve(&input)?;
-        println!("{result}");
+    #[test_log::test]
+    fn streaming() -> Result<(), Box<dyn Error>> {
+        use super::problem_1::solve_reader;
+        use std::{fs::File, io::BufReader};
+
+        let input = BufReader::new(File::open("resources/day_07/train_problem_1.inp")?);
+        assert_eq!(solve_reader(input)?, 6440);
+
+        let error = solve_reader("32T3K 765\nT55X5 684\n".as_bytes()).unwrap_err();
+        assert_eq!(format!("{error:#}"), "Line 2: Parsing hand: Unknown card X");
         Ok(())
     }
+
+    proptest! {
+        #[test]
+        fn wildcards_pick_best_type(seed: u64) {
+            use super::{Card1, Card2, Hand, HandType};
+            use crate::generators::{day_07::hand, rng};
+
+            let cards = hand(&mut rng(seed));
+            let wild: HandType = (&format!("{cards} 1").parse::<Hand<Card2>>().unwrap()).into();
+            // Try every card in place of the jokers and keep the best type.
+            let best = "AKQT98765432"
+                .chars()
+                .map(|card| {
+                    let cards = cards.replace('J', &card.to_string());
+                    HandType::from(&format!("{cards} 1").parse::<Hand<Card1>>().unwrap())
+                })
+                .max()
+                .unwrap();
+            prop_assert_eq!(wild, best);
+        }
+    }
 }
@@ -1,16 +1,35 @@
-use anyhow::{anyhow, Context, Result};
-use regex::Regex;
-use std::{
-    collections::HashMap,
-    ops::{Deref, DerefMut},
+//! Day 8: Haunted Wasteland. The model is the [`Data`] of a map: the
+//! left/right indications and the network of nodes they are followed over.
+
+use anyhow::{anyhow, bail, Context, Result};
+use log::debug;
+use nom::{
+    character::complete::{alphanumeric1, none_of},
+    multi::many1,
+    sequence::tuple,
 };
+use num::Integer;
+use std::{collections::HashMap, fmt, str::FromStr};
 
-#[derive(Debug)]
-enum Indication {
+use crate::parsers::{blank_line, convert, key_pair, lines, parse, IResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Indication {
     Right,
     Left,
 }
 
+impl fmt::Display for Indication {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tag = match self {
+            Indication::Right => 'R',
+            Indication::Left => 'L',
+        };
+        write!(f, "{tag}")
+    }
+}
+
 impl TryFrom<char> for Indication {
     type Error = anyhow::Error;
     fn try_from(value: char) -> Result<Self, Self::Error> {
@@ -22,214 +41,441 @@ impl TryFrom<char> for Indication {
     }
 }
 
-#[derive(Clone)]
-struct Directions(HashMap<String, Direction>);
+// Both ways out of a node, as interned ids.
+#[derive(Debug, Clone, Copy)]
+struct Direction {
+    right: usize,
+    left: usize,
+}
 
-impl Deref for Directions {
-    type Target = HashMap<String, Direction>;
-    fn deref(&self) -> &Self::Target {
-        &self.0
+impl Direction {
+    fn to(&self, indication: Indication) -> usize {
+        match indication {
+            Indication::Right => self.right,
+            Indication::Left => self.left,
+        }
     }
 }
 
-impl DerefMut for Directions {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+// A node as written, naming where each way out of it leads.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Node {
+    name: String,
+    left: String,
+    right: String,
+}
+
+// The map as written, which interning has to check again when deserializing.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Network {
+    indications: Vec<Indication>,
+    nodes: Vec<Node>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Data> for Network {
+    fn from(data: Data) -> Network {
+        let nodes = data
+            .directions
+            .iter()
+            .enumerate()
+            .map(|(id, direction)| Node {
+                name: data.names[id].clone(),
+                left: data.names[direction.left].clone(),
+                right: data.names[direction.right].clone(),
+            })
+            .collect();
+        Network {
+            indications: data.indications,
+            nodes,
+        }
     }
 }
 
-impl Directions {
-    fn new() -> Directions {
-        Directions(HashMap::new())
+#[cfg(feature = "serde")]
+impl TryFrom<Network> for Data {
+    type Error = anyhow::Error;
+
+    fn try_from(network: Network) -> Result<Data> {
+        Data::new(network.indications, network.nodes)
     }
+}
+
+/// The indications and the network, with node names interned to dense ids.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "Network", try_from = "Network")
+)]
+pub struct Data {
+    indications: Vec<Indication>,
+    // Node names indexed by their interned id, as defined.
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    directions: Vec<Direction>,
+}
+
+impl Data {
+    fn new(indications: Vec<Indication>, nodes: Vec<Node>) -> Result<Data> {
+        // Names get dense ids in the order nodes are defined, so the map can
+        // be written back as it was read.
+        let mut ids = HashMap::new();
+        let mut names = vec![];
+        for node in &nodes {
+            if ids.insert(node.name.clone(), names.len()).is_some() {
+                bail!("Node {} is defined twice", node.name);
+            }
+            names.push(node.name.clone());
+        }
 
-    fn add_direction(&mut self, from: &str, right: &str, left: &str) {
-        self.insert(
-            from.to_string(),
-            Direction {
-                right: right.to_string(),
-                left: left.to_string(),
-            },
+        let id = |name: &str| {
+            ids.get(name)
+                .copied()
+                .with_context(|| format!("Node {name} is never defined"))
+        };
+        let directions = nodes
+            .iter()
+            .map(|node| {
+                Ok(Direction {
+                    left: id(&node.left)?,
+                    right: id(&node.right)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        debug!(
+            "Parsed {} indications and {} nodes",
+            indications.len(),
+            directions.len()
         );
+
+        Ok(Data {
+            indications,
+            names,
+            ids,
+            directions,
+        })
     }
 
-    fn next(&self, indication: &Indication, from: &str) -> anyhow::Result<&str> {
-        Ok(self
-            .0
-            .get(from)
-            .context("getting directions")?
-            .to(indication))
+    fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
     }
-}
 
-#[derive(Clone)]
-struct Direction {
-    right: String,
-    left: String,
-}
+    pub fn indications(&self) -> &[Indication] {
+        &self.indications
+    }
 
-impl Direction {
-    fn to(&self, indication: &Indication) -> &str {
-        match indication {
-            Indication::Right => &self.right,
-            Indication::Left => &self.left,
+    /// Node names in the order they are defined.
+    pub fn nodes(&self) -> impl Iterator<Item = &str> + '_ {
+        self.names.iter().map(String::as_str)
+    }
+
+    /// Where `indication` leads from the node named `from`.
+    pub fn follow(&self, from: &str, indication: Indication) -> Option<&str> {
+        let node = self.id(from)?;
+        Some(&self.names[self.next(indication, node)])
+    }
+
+    /// Steps from AAA to ZZZ.
+    pub fn solve_1(&self) -> Result<usize> {
+        let start = self.id("AAA").context("There is no AAA node")?;
+        let end = self.id("ZZZ").context("There is no ZZZ node")?;
+        let steps = self.walk(start, |node| node == end);
+
+        debug!("Reached ZZZ in {steps} steps");
+        Ok(steps)
+    }
+
+    /// Steps until ghosts starting on every node ending with A all stand on
+    /// nodes ending with Z.
+    pub fn solve_2(&self) -> Result<usize> {
+        let ends: Vec<bool> = self.names.iter().map(|name| name.ends_with('Z')).collect();
+        let starts: Vec<usize> = self.nodes_ending_with('A').collect();
+        debug!("Walking {} starting nodes", starts.len());
+
+        let mut cycles = vec![];
+        for start in starts {
+            let cycle = self.walk(start, |node| ends[node]);
+
+            debug!(
+                "Start {} reaches an end node after {cycle} steps",
+                self.names[start]
+            );
+            cycles.push(cycle);
         }
+
+        let steps = cycles
+            .into_iter()
+            .reduce(|lcm: usize, c| lcm.lcm(&c))
+            .context("There are no starting nodes")?;
+        debug!("All ghosts meet after {steps} steps");
+        Ok(steps)
     }
-}
 
-pub struct Data {
-    indications: Vec<Indication>,
-    directions: Directions,
+    /// Builds the tables answering where a ghost stands after any number of
+    /// steps.
+    pub fn jumps(&self) -> Jumps<'_> {
+        // Node reached after one full pass over the indications.
+        let pass: Vec<usize> = (0..self.names.len())
+            .map(|node| {
+                self.indications
+                    .iter()
+                    .fold(node, |node, &indication| self.next(indication, node))
+            })
+            .collect();
+
+        // Level i jumps 2^i passes ahead.
+        let mut table = vec![pass];
+        for _ in 1..usize::BITS {
+            let last = table.last().unwrap();
+            let level = last.iter().map(|&node| last[node]).collect();
+            table.push(level);
+        }
+
+        Jumps { data: self, table }
+    }
+
+    fn next(&self, indication: Indication, from: usize) -> usize {
+        self.directions[from].to(indication)
+    }
+
+    fn nodes_ending_with(&self, tag: char) -> impl Iterator<Item = usize> + '_ {
+        self.names
+            .iter()
+            .enumerate()
+            .filter(move |(_, name)| name.ends_with(tag))
+            .map(|(id, _)| id)
+    }
+
+    // Steps from `start` until `end` holds, following the indications.
+    fn walk(&self, start: usize, end: impl Fn(usize) -> bool) -> usize {
+        let mut node = start;
+        let mut steps = 0;
+        for &indication in self.indications.iter().cycle() {
+            if end(node) {
+                break;
+            }
+            node = self.next(indication, node);
+            steps += 1;
+        }
+        steps
+    }
 }
 
 #[aoc_generator(day8)]
 pub fn input_generator(input: &str) -> Result<Data> {
-    let mut map = input.lines();
-    let indications = map
-        .next()
-        .context("Getting first line")?
-        .chars()
-        .map(|c| c.try_into())
-        .collect::<Result<Vec<Indication>>>()
-        .context("Parsing indications")?;
-    let map = map.skip(1);
-
-    let parse = Regex::new(r#"(?<from>[A-Z]{3}) = \((?<left>[A-Z]{3}), (?<right>[A-Z]{3})\)"#)?;
-
-    let directions = map.fold(Directions::new(), |mut dir, direction| {
-        let caps = parse.captures(direction).unwrap();
-        dir.add_direction(&caps["from"], &caps["right"], &caps["left"]);
-        dir
-    });
-
-    Ok(Data {
-        indications,
-        directions,
-    })
+    input.parse()
 }
 
-#[aoc(day8, part1)]
-pub fn solve_1(input: &Data) -> Result<usize> {
-    let Data {
-        indications,
-        directions,
-    } = input;
-    let steps = indications
-        .iter()
-        .cycle()
-        .scan("AAA", |from, indication| {
-            (from != &"ZZZ").then(|| {
-                *from = directions.next(indication, from).unwrap();
-                Some(())
-            })
-        })
-        .count();
+impl FromStr for Data {
+    type Err = anyhow::Error;
 
-    Ok(steps)
+    fn from_str(input: &str) -> Result<Data> {
+        let (indications, _, nodes) = parse(input, tuple((indications, blank_line, lines(node))))?;
+        Data::new(indications, nodes)
+    }
 }
 
-use num::Integer;
+fn indications(input: &str) -> IResult<'_, Vec<Indication>> {
+    many1(convert(none_of("\r\n"), Indication::try_from))(input)
+}
 
-#[aoc(day8, part2)]
-pub fn solve_2(input: &Data) -> Result<usize> {
-    let Data {
-        indications,
-        directions,
-    } = input;
-
-    let starts: Vec<String> = directions
-        .keys()
-        .filter(|start| start.chars().last() == Some('A'))
-        .map(|start| start.to_string())
-        .collect();
-
-    let mut cycles = vec![];
-    for start in starts {
-        let indications = indications.iter().cycle();
-        let cycle = indications
-            .scan((0, start), |state, indication| {
-                if state.1.chars().last() == Some('Z') {
-                    return None;
-                }
-                *state = (
-                    state.0 + 1,
-                    directions.next(indication, &state.1).unwrap().to_string(),
-                );
-                Some(state.0)
-            })
-            .last()
-            .unwrap();
+// A node and both ways out of it, as in `AAA = (BBB, CCC)`.
+fn node(input: &str) -> IResult<'_, Node> {
+    let (input, (name, (left, right))) = key_pair(alphanumeric1, alphanumeric1)(input)?;
+    Ok((
+        input,
+        Node {
+            name: name.to_string(),
+            left: left.to_string(),
+            right: right.to_string(),
+        },
+    ))
+}
+
+impl fmt::Display for Data {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for indication in &self.indications {
+            write!(f, "{indication}")?;
+        }
+        writeln!(f)?;
+        for (name, direction) in self.names.iter().zip(&self.directions) {
+            let (left, right) = (&self.names[direction.left], &self.names[direction.right]);
+            write!(f, "\n{name} = ({left}, {right})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Binary lifting tables over full passes of the indications of a [`Data`].
+pub struct Jumps<'a> {
+    data: &'a Data,
+    table: Vec<Vec<usize>>,
+}
+
+impl<'a> Jumps<'a> {
+    /// Node reached after a full pass over the indications starting at `from`.
+    pub fn pass(&self, from: &str) -> Option<&'a str> {
+        let node = self.data.id(from)?;
+        Some(&self.data.names[self.table[0][node]])
+    }
 
-        cycles.push(cycle);
+    /// Node reached after `steps` steps starting at `from`. Whole passes are
+    /// jumped in time logarithmic in `steps`, leaving fewer steps than there
+    /// are indications to walk one by one.
+    pub fn after(&self, from: &str, steps: usize) -> Option<&'a str> {
+        let length = self.data.indications.len();
+        let mut node = self.data.id(from)?;
+        if length == 0 {
+            return Some(&self.data.names[node]);
+        }
+
+        let mut passes = steps / length;
+        for level in &self.table {
+            if passes == 0 {
+                break;
+            }
+            if passes & 1 == 1 {
+                node = level[node];
+            }
+            passes >>= 1;
+        }
+
+        for &indication in &self.data.indications[..steps % length] {
+            node = self.data.next(indication, node);
+        }
+        Some(&self.data.names[node])
     }
+}
 
-    cycles
-        .into_iter()
-        .reduce(|lcm: usize, c| lcm.lcm(&c))
-        .context("")
+#[aoc(day8, part1)]
+pub fn solve_1(input: &Data) -> Result<usize> {
+    input.solve_1()
+}
+
+#[aoc(day8, part2)]
+pub fn solve_2(input: &Data) -> Result<usize> {
+    input.solve_2()
 }
 
 #[cfg(test)]
 mod test {
 
-    #[allow(unused_imports)]
     use std::error::Error;
-    #[allow(unused_imports)]
     use std::fs::read_to_string;
-    #[cfg(feature = "problem_1")]
-    const P1_TRAIN_1_SOLUTION: usize = 2;
-    const P1_TRAIN_2_SOLUTION: usize = 6;
-
-    #[cfg(feature = "problem_2")]
-    const P2_TRAIN_SOLUTION: usize = 6;
-
-    #[test]
-    #[cfg(feature = "problem_1")]
-    fn train_problem_1_1() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
-        let input = read_to_string("resources/day_08/train_problem_1_1.inp")?;
-        let result = solve(&input)?;
-        assert_eq!(result, P1_TRAIN_1_SOLUTION);
+
+    use proptest::prelude::*;
+
+    use super::{input_generator, solve_2, Data};
+
+    #[test_log::test]
+    fn data() -> Result<(), Box<dyn Error>> {
+        use super::Indication;
+
+        let data: Data = read_to_string("resources/day_08/train_problem_1_2.inp")?.parse()?;
+        assert_eq!(
+            data.indications(),
+            &[Indication::Left, Indication::Left, Indication::Right]
+        );
+        assert_eq!(data.nodes().collect::<Vec<_>>(), ["AAA", "BBB", "ZZZ"]);
+        assert_eq!(data.follow("AAA", Indication::Right), Some("BBB"));
+        assert_eq!(data.follow("CCC", Indication::Right), None);
+        assert_eq!(data.solve_1()?, 6);
         Ok(())
     }
 
-    #[test]
-    #[cfg(feature = "problem_1")]
-    fn train_problem_1_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
-        let input = read_to_string("resources/day_08/train_problem_1_2.inp")?;
-        let result = solve(&input)?;
-        assert_eq!(result, P1_TRAIN_2_SOLUTION);
+    #[test_log::test]
+    fn display_round_trip() -> Result<(), Box<dyn Error>> {
+        for (path, input) in crate::resources::inputs(8)? {
+            let data: Data = input.parse()?;
+            assert_eq!(data.to_string(), input.trim_end(), "{path:?}");
+            let printed: Data = data.to_string().parse()?;
+            assert_eq!(printed.to_string(), data.to_string());
+        }
         Ok(())
     }
 
-    #[test]
-    #[cfg(feature = "problem_1")]
-    fn solve_problem_1() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
-        let input = read_to_string("resources/day_08/problem_1.inp")?;
-        let result = solve(&input)?;
-        println!("{result}");
-        Ok(())
+    #[test_log::test]
+    fn undefined_nodes() {
+        assert!(input_generator("L\n\nAAA = (ZZZ, ZZZ)\n").is_err());
+        assert!(input_generator("L\n\nAAA = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)\n").is_ok());
+        assert!(input_generator("L\n\nAAA = (AAA, A-A)\n").is_err());
     }
 
-    #[test]
-    #[cfg(feature = "problem_2")]
-    fn train_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_2::solve;
+    #[test_log::test]
+    fn train_jumps() -> Result<(), Box<dyn Error>> {
         let input = read_to_string("resources/day_08/train_problem_2.inp")?;
-        let result = solve(&input)?;
-        assert_eq!(result, P2_TRAIN_SOLUTION);
+        let input = input_generator(&input)?;
+        let jumps = input.jumps();
+        assert_eq!(jumps.pass("11A"), Some("11Z"));
+        assert_eq!(jumps.after("11A", 0), Some("11A"));
+        assert_eq!(jumps.after("22A", 3), Some("22Z"));
+        assert_eq!(jumps.after("22A", 6_000_000_000_003), Some("22Z"));
+        assert_eq!(jumps.after("22A", usize::MAX), Some("22Z"));
+        assert_eq!(jumps.after("33A", 1), None);
         Ok(())
     }
 
-    #[test]
-    #[cfg(feature = "problem_2")]
-    fn solve_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_2::solve;
-        let input = read_to_string("resources/day_08/problem_2.inp")?;
-        let result = solve(&input)?;
-        println!("{result}");
-        Ok(())
+    // Reference for part 2 that moves every ghost in lockstep until all of
+    // them stand on an end node at once.
+    fn brute_force_problem_2(input: &Data) -> usize {
+        let mut ghosts: Vec<usize> = input.nodes_ending_with('A').collect();
+
+        let mut steps = 0;
+        for &indication in input.indications.iter().cycle() {
+            if ghosts
+                .iter()
+                .all(|&ghost| input.names[ghost].ends_with('Z'))
+            {
+                break;
+            }
+            for ghost in ghosts.iter_mut() {
+                *ghost = input.next(indication, *ghost);
+            }
+            steps += 1;
+        }
+        steps
+    }
+
+    proptest! {
+        #[test]
+        fn lcm_matches_brute_force(
+            seed: u64,
+            ghosts in 1usize..4,
+            instructions in 1usize..5,
+            max_multiple in 1usize..6,
+        ) {
+            use crate::generators::{day_08::{generate_ghosts, GhostParams}, rng};
+
+            let params = GhostParams {
+                ghosts,
+                instructions,
+                max_multiple,
+            };
+            let input = generate_ghosts(&mut rng(seed), &params);
+            let input = input_generator(&input).unwrap();
+            prop_assert_eq!(solve_2(&input).unwrap(), brute_force_problem_2(&input));
+        }
+
+        #[test]
+        fn jumps_match_walking(seed: u64, nodes in 1usize..20, instructions in 1usize..10, steps in 0usize..500) {
+            use crate::generators::{day_08::{generate, Params}, rng};
+
+            let input = generate(&mut rng(seed), &Params { nodes, instructions });
+            let input = input_generator(&input).unwrap();
+            let jumps = input.jumps();
+            for (id, name) in input.names.iter().enumerate() {
+                let walked = input
+                    .indications
+                    .iter()
+                    .cycle()
+                    .take(steps)
+                    .fold(id, |node, &indication| input.next(indication, node));
+                prop_assert_eq!(jumps.after(name, steps), Some(input.names[walked].as_str()));
+            }
+        }
     }
 }
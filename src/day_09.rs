@@ -1,144 +1,354 @@
-pub mod problem_1 {
+//! Day 9: Mirage Maintenance. The model is a [`Report`] of [`History`]
+//! values, generic over the [`Value`] they are extrapolated with.
 
-    use anyhow::{Context, Result};
+use std::{
+    fmt::{self, Display},
+    io::BufRead,
+    str::FromStr,
+};
 
-    fn construct_deltas(history: Vec<isize>) -> Result<Vec<isize>> {
-        let mut deltas = Vec::new();
-        deltas.push(history);
+use anyhow::{bail, Context, Result};
+use log::trace;
+use num::{CheckedAdd, CheckedSub, Zero};
 
-        loop {
-            let base = deltas.last().unwrap();
+use crate::parsers::{lines, numbers, parse};
+use crate::streaming::try_fold_lines;
 
-            if base.iter().all(|&value| value == 0) {
-                break;
-            }
+/// Values a history can be extrapolated with. Every operation is checked, so
+/// fixed-width integers report overflow instead of wrapping.
+pub trait Value: FromStr + Clone + Zero + CheckedAdd + CheckedSub + Display {}
 
-            deltas.push(base.windows(2).map(|a| a[1] - a[0]).collect::<Vec<_>>());
+impl<T> Value for T where T: FromStr + Clone + Zero + CheckedAdd + CheckedSub + Display {}
+
+/// The readings of one value over time, oldest first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct History<T = isize> {
+    values: Vec<T>,
+}
+
+impl<T: Value> FromStr for History<T> {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(History {
+            values: parse(s, numbers)?,
+        })
+    }
+}
+
+impl<T: Display> Display for History<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, value) in self.values.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{value}")?;
         }
+        Ok(())
+    }
+}
 
-        deltas
+impl<T> History<T> {
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+}
+
+impl<T: Value> History<T> {
+    /// The value that comes after the last reading.
+    pub fn next(&self) -> Result<T> {
+        next(self.values.clone())
+    }
+
+    /// The value that came before the first reading.
+    pub fn previous(&self) -> Result<T> {
+        previous(self.values.clone())
+    }
+}
+
+/// Every history in the report, one per line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Report<T = isize> {
+    histories: Vec<History<T>>,
+}
+
+impl<T: Value> FromStr for Report<T> {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let histories = parse(s, lines(numbers))?
             .into_iter()
-            .map(|deltas| {
-                deltas
-                    .last()
-                    .map(|value| value.clone())
-                    .context("level is empty")
-            })
-            .collect::<Result<Vec<_>>>()
+            .map(|values| History { values })
+            .collect();
+        Ok(Report { histories })
     }
+}
 
-    pub fn solve(input: &str) -> Result<isize> {
-        let histories = input
-            .lines()
-            .map(|history| {
-                history
-                    .split_whitespace()
-                    .map(|value| value.parse().context("parsing value"))
-                    .collect::<Result<Vec<isize>>>()
+impl<T: Display> Display for Report<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, history) in self.histories.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{history}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Value> Report<T> {
+    pub fn histories(&self) -> &[History<T>] {
+        &self.histories
+    }
+
+    /// The sum of the next value of every history.
+    pub fn solve_1(&self) -> Result<T> {
+        self.sum(History::next)
+    }
+
+    /// The sum of the previous value of every history.
+    pub fn solve_2(&self) -> Result<T> {
+        self.sum(History::previous)
+    }
+
+    // Sums what `extrapolate` makes of every history.
+    fn sum(&self, extrapolate: fn(&History<T>) -> Result<T>) -> Result<T> {
+        self.histories.iter().try_fold(T::zero(), |sum, history| {
+            let value = extrapolate(history)?;
+            sum.checked_add(&value)
+                .with_context(|| overflow(&sum, "+", &value))
+        })
+    }
+}
+
+// The history followed by its differences, level after level, until they are
+// all zeros.
+fn construct_deltas<T: Value>(history: Vec<T>) -> Result<Vec<Vec<T>>> {
+    if history.is_empty() {
+        bail!("history is empty");
+    }
+
+    let mut deltas = vec![history];
+    loop {
+        let base = deltas.last().unwrap();
+
+        if base.iter().all(|value| value.is_zero()) {
+            break;
+        }
+
+        let next = base
+            .windows(2)
+            .map(|a| {
+                a[1].checked_sub(&a[0])
+                    .with_context(|| overflow(&a[1], "-", &a[0]))
             })
-            .collect::<Result<Vec<Vec<isize>>>>()?;
+            .collect::<Result<Vec<_>>>()?;
+        deltas.push(next);
+    }
+    trace!("History reduced to zeros after {} levels", deltas.len());
+    Ok(deltas)
+}
 
-        let result = histories
-            .into_iter()
-            .map(|history| Ok(construct_deltas(history)?.into_iter().rev().sum::<isize>()))
-            .collect::<Result<Vec<_>>>()?
-            .into_iter()
-            .sum();
+fn overflow<T: Display>(a: &T, op: &str, b: &T) -> String {
+    format!("overflow computing {a} {op} {b}")
+}
+
+// Sums what `extrapolate` makes of every history, one line at a time.
+fn sum_lines<T: Value, R: BufRead>(reader: R, extrapolate: fn(Vec<T>) -> Result<T>) -> Result<T> {
+    try_fold_lines(reader, T::zero(), |sum, line| {
+        let value = extrapolate(parse(line, numbers)?)?;
+        sum.checked_add(&value)
+            .with_context(|| overflow(&sum, "+", &value))
+    })
+}
+
+fn next<T: Value>(history: Vec<T>) -> Result<T> {
+    // A level running out of values only happens past a constant one, so it
+    // adds nothing.
+    let next = construct_deltas(history)?
+        .into_iter()
+        .rev()
+        .filter_map(|deltas| deltas.last().cloned())
+        .try_fold(T::zero(), |next, value| {
+            value
+                .checked_add(&next)
+                .with_context(|| overflow(&value, "+", &next))
+        })?;
+    trace!("Extrapolated next value: {next}");
+    Ok(next)
+}
+
+fn previous<T: Value>(history: Vec<T>) -> Result<T> {
+    let previous = construct_deltas(history)?
+        .into_iter()
+        .rev()
+        .filter_map(|deltas| deltas.first().cloned())
+        .try_fold(T::zero(), |previous, value| {
+            value
+                .checked_sub(&previous)
+                .with_context(|| overflow(&value, "-", &previous))
+        })?;
+    trace!("Extrapolated previous value: {previous}");
+    Ok(previous)
+}
+
+pub mod problem_1 {
+
+    use std::io::BufRead;
+
+    use super::{next, sum_lines, Value};
+    use anyhow::Result;
+    use log::debug;
+    use num::BigInt;
+
+    /// Extrapolates with any [`Value`], such as [`BigInt`] for histories too
+    /// steep for `isize`, reading one history at a time.
+    pub fn solve_reader_with<T: Value, R: BufRead>(reader: R) -> Result<T> {
+        let result = sum_lines(reader, next)?;
+        debug!("Sum of extrapolated values: {result}");
         Ok(result)
     }
+
+    pub fn solve_with<T: Value>(input: &str) -> Result<T> {
+        solve_reader_with(input.as_bytes())
+    }
+
+    pub fn solve_reader<R: BufRead>(reader: R) -> Result<isize> {
+        solve_reader_with(reader)
+    }
+
+    pub fn solve(input: &str) -> Result<isize> {
+        solve_with(input)
+    }
+
+    pub fn solve_big(input: &str) -> Result<BigInt> {
+        solve_with(input)
+    }
 }
 
-#[cfg(feature = "problem_2")]
 pub mod problem_2 {
 
+    use std::io::BufRead;
+
+    use super::{previous, sum_lines, Value};
     use anyhow::Result;
+    use log::debug;
+    use num::BigInt;
+
+    /// Extrapolates backwards with any [`Value`], such as [`BigInt`] for
+    /// histories too steep for `isize`, reading one history at a time.
+    pub fn solve_reader_with<T: Value, R: BufRead>(reader: R) -> Result<T> {
+        let result = sum_lines(reader, previous)?;
+        debug!("Sum of extrapolated values: {result}");
+        Ok(result)
+    }
+
+    pub fn solve_with<T: Value>(input: &str) -> Result<T> {
+        solve_reader_with(input.as_bytes())
+    }
+
+    pub fn solve_reader<R: BufRead>(reader: R) -> Result<isize> {
+        solve_reader_with(reader)
+    }
 
     pub fn solve(input: &str) -> Result<isize> {
-        let histories = input
-            .lines()
-            .map(|history| {
-                history
-                    .split_whitespace()
-                    .map(|value| value.parse().unwrap())
-                    .collect::<Vec<isize>>()
-            })
-            .collect::<Vec<Vec<isize>>>();
-        let histories: isize = histories
-            .into_iter()
-            .map(|h| {
-                let mut deltas: Vec<Vec<isize>> = Vec::new();
-                deltas.push(h);
-
-                loop {
-                    let base = deltas.last().unwrap();
-                    if base.iter().all(|&a| a == 0) {
-                        break;
-                    }
-                    deltas.push(base.windows(2).map(|s| s[1] - s[0]).collect::<Vec<_>>());
-                }
-
-                deltas
-                    .into_iter()
-                    .map(|f| f.first().unwrap().clone())
-                    .collect::<Vec<_>>()
-            })
-            .map(|e| e.into_iter().rev().fold(0, |r, v| v - r))
-            .sum();
+        solve_with(input)
+    }
 
-        Ok(histories)
+    pub fn solve_big(input: &str) -> Result<BigInt> {
+        solve_with(input)
     }
 }
 
 #[cfg(test)]
 mod test {
 
-    #[allow(unused_imports)]
     use std::error::Error;
-    #[allow(unused_imports)]
     use std::fs::read_to_string;
-    #[cfg(feature = "problem_1")]
-    const P1_TRAIN_SOLUTION: isize = 114;
 
-    #[cfg(feature = "problem_2")]
+    const P1_TRAIN_SOLUTION: isize = 114;
     const P2_TRAIN_SOLUTION: isize = 2;
 
-    #[test]
-    #[cfg(feature = "problem_1")]
-    fn train_problem_1() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
+    #[test_log::test]
+    fn report() -> Result<(), Box<dyn Error>> {
+        use super::Report;
+        use num::BigInt;
+
         let input = read_to_string("resources/day_09/train_problem_1.inp")?;
-        let result = solve(&input)?;
-        assert_eq!(result, P1_TRAIN_SOLUTION);
+        let report: Report = input.parse()?;
+        let history = &report.histories()[2];
+        assert_eq!(history.values(), &[10, 13, 16, 21, 30, 45]);
+        assert_eq!(history.next()?, 68);
+        assert_eq!(report.solve_1()?, 114);
+        assert_eq!((history.previous()?, report.solve_2()?), (5, 2));
+
+        let steep: Report<BigInt> = read_to_string("resources/day_09/train_steep.inp")?.parse()?;
+        assert_eq!(steep.solve_1()?, "-27670116110564327421".parse()?);
+        assert_eq!(steep.histories()[0].values().len(), 3);
+        assert!("1 2 x".parse::<Report>().is_err());
         Ok(())
     }
 
-    #[test]
-    #[cfg(feature = "problem_1")]
-    fn solve_problem_1() -> Result<(), Box<dyn Error>> {
-        use super::problem_1::solve;
-        let input = read_to_string("resources/day_09/problem_1.inp")?;
-        let result = solve(&input)?;
-        println!("{result}");
+    #[test_log::test]
+    fn display_round_trip() -> Result<(), Box<dyn Error>> {
+        use super::Report;
+        use num::BigInt;
+
+        for (path, input) in crate::resources::inputs(9)? {
+            let report: Report<BigInt> = input.parse()?;
+            assert_eq!(report.to_string(), input.trim_end(), "{path:?}");
+            assert_eq!(report.to_string().parse::<Report<BigInt>>()?, report);
+        }
         Ok(())
     }
 
-    #[test]
-    #[cfg(feature = "problem_2")]
-    fn train_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_2::solve;
-        let input = read_to_string("resources/day_09/train_problem_2.inp")?;
-        let result = solve(&input)?;
-        assert_eq!(result, P2_TRAIN_SOLUTION);
+    #[test_log::test]
+    fn steep_histories() -> Result<(), Box<dyn Error>> {
+        use super::{problem_1, Report};
+        use num::BigInt;
+
+        let input = read_to_string("resources/day_09/train_steep.inp")?;
+        let report: Report = input.parse()?;
+        assert!(report.solve_1().is_err());
+        assert!(report.solve_2().is_err());
+        let report: Report<BigInt> = input.parse()?;
+        let expected = "-27670116110564327421".parse::<BigInt>()?;
+        assert_eq!(report.solve_1()?, expected);
+        assert_eq!(report.solve_2()?, expected);
+        assert_eq!(problem_1::solve_big(&input)?, expected);
+
+        // Big integers agree with isize whenever it doesn't overflow.
+        let input = read_to_string("resources/day_09/train_problem_1.inp")?;
+        let report: Report<BigInt> = input.parse()?;
+        assert_eq!(report.solve_1()?, BigInt::from(P1_TRAIN_SOLUTION));
+        assert_eq!(report.solve_2()?, BigInt::from(P2_TRAIN_SOLUTION));
+
+        assert!(problem_1::solve("1 2 x").is_err());
+        assert!(problem_1::solve("1 2\n\n3").is_err());
         Ok(())
     }
 
-    #[test]
-    #[cfg(feature = "problem_2")]
-    fn solve_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::problem_2::solve;
-        let input = read_to_string("resources/day_09/problem_2.inp")?;
-        let result = solve(&input)?;
-        println!("{result}");
+    #[test_log::test]
+    fn streaming() -> Result<(), Box<dyn Error>> {
+        use super::problem_1::{solve_reader, solve_reader_with};
+        use num::BigInt;
+        use std::{fs::File, io::BufReader};
+
+        let input = BufReader::new(File::open("resources/day_09/train_problem_1.inp")?);
+        assert_eq!(solve_reader(input)?, P1_TRAIN_SOLUTION);
+        let input = BufReader::new(File::open("resources/day_09/train_steep.inp")?);
+        assert_eq!(
+            solve_reader_with::<BigInt, _>(input)?,
+            "-27670116110564327421".parse::<BigInt>()?
+        );
+
+        let error = solve_reader("1 2 3\n4 five 6\n".as_bytes()).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            r#"Line 2: Unexpected "five" at column 3"#
+        );
         Ok(())
     }
 }
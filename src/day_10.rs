@@ -1,9 +1,17 @@
+//! Day 10: Pipe Maze. The model is the [`Input`] grid of tiles, with the
+//! [`PipeLoop`] through its start found on demand.
+
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{HashMap, HashSet},
+    fmt,
     ops::Deref,
+    str::FromStr,
 };
 
 use anyhow::{bail, Context, Result};
+use log::{debug, trace};
+
+use crate::parsers::{grid, parse};
 
 #[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Copy)]
 enum Dir {
@@ -25,7 +33,7 @@ impl Dir {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Tile(char);
 
 impl Tile {
@@ -34,7 +42,7 @@ impl Tile {
         possible
             .contains(tile)
             .then_some(Tile(tile))
-            .context("Tile {tile} is not part of the dataset")
+            .with_context(|| format!("Tile {tile} is not part of the dataset"))
     }
 
     fn redirect(&self, from: &Dir) -> Result<Dir> {
@@ -87,6 +95,7 @@ impl Deref for Tile {
     }
 }
 
+#[derive(Debug, Clone)]
 struct Space {
     w: usize,
     h: usize,
@@ -117,109 +126,212 @@ impl Space {
     }
 }
 
+/// The tiles as (row, column) from the top left, and where the animal
+/// starts.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "Rows", try_from = "Rows")
+)]
 pub struct Input {
     start: (usize, usize),
     space: Space,
 }
 
+// The tiles row by row as written, parsed again when deserializing.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Rows {
+    start: (usize, usize),
+    rows: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Input> for Rows {
+    fn from(input: Input) -> Rows {
+        let rows = input
+            .space
+            .tiles
+            .iter()
+            .map(|row| row.iter().map(|tile| tile.0).collect())
+            .collect();
+        Rows {
+            start: input.start,
+            rows,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<Rows> for Input {
+    type Error = anyhow::Error;
+
+    fn try_from(rows: Rows) -> Result<Input> {
+        let input: Input = rows.rows.join("\n").parse()?;
+        if input.start != rows.start {
+            bail!("Start is at {:?}, not {:?}", input.start, rows.start);
+        }
+        Ok(input)
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (r, row) in self.space.tiles.iter().enumerate() {
+            if r > 0 {
+                writeln!(f)?;
+            }
+            for tile in row {
+                write!(f, "{}", tile.0)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Input {
+    pub fn width(&self) -> usize {
+        self.space.w
+    }
+
+    pub fn height(&self) -> usize {
+        self.space.h
+    }
+
+    pub fn start(&self) -> (usize, usize) {
+        self.start
+    }
+
+    pub fn tile(&self, position: (usize, usize)) -> Option<char> {
+        self.space.get(&position).map(|tile| tile.0)
+    }
+
+    pub fn find_loop(&self) -> Result<PipeLoop> {
+        find_loop(self)
+    }
+
+    /// Steps to the point of the loop furthest from the start.
+    pub fn solve_1(&self) -> Result<usize> {
+        let furthest = self.find_loop()?.length() / 2;
+        debug!("Furthest point at {furthest}");
+        Ok(furthest)
+    }
+
+    /// Tiles enclosed by the loop, counted the default way.
+    pub fn solve_2(&self) -> Result<usize> {
+        self.solve_2_with(Strategy::Scan)
+    }
+
+    pub fn solve_2_with(&self, strategy: Strategy) -> Result<usize> {
+        let pipe_loop = self.find_loop()?;
+        strategy.enclosed(&self.space, &pipe_loop)
+    }
+}
+
 #[aoc_generator(day10)]
 pub fn input_generator(input: &str) -> Result<Input> {
-    let space: Result<Vec<Vec<Tile>>> = input
-        .lines()
-        .map(|row| {
-            row.chars()
-                .map(|tile| Tile::new(tile))
-                .collect::<Result<Vec<Tile>>>()
-        })
-        .collect();
-    let space = space?;
-    let h = space.len();
-    let w = space.first().unwrap().len();
-    let space = Space { tiles: space, h, w };
-
-    let mut start = None;
-    'start_row: for (r, row) in space.tiles.iter().enumerate() {
-        for (c, tile) in row.iter().enumerate() {
-            if **tile == 'S' {
-                start = Some((r, c));
-                break 'start_row;
+    input.parse()
+}
+
+impl FromStr for Input {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Input> {
+        let space = parse(input, grid(Tile::new))?;
+        let h = space.len();
+        let w = space.first().context("Space has no rows")?.len();
+        let space = Space { tiles: space, h, w };
+
+        let mut start = None;
+        'start_row: for (r, row) in space.tiles.iter().enumerate() {
+            for (c, tile) in row.iter().enumerate() {
+                if **tile == 'S' {
+                    start = Some((r, c));
+                    break 'start_row;
+                }
             }
         }
+        let start = start.context("Looking for start position")?;
+        debug!(
+            "Parsed {h}x{w} space with start at {start:?}",
+            h = space.h,
+            w = space.w
+        );
+
+        Ok(Input { start, space })
     }
-    let start = start.context("Looking for start position")?;
-
-    Ok(Input { start, space })
 }
 
-#[aoc(day10, part1)]
-pub fn solve_1(input: &Input) -> Result<usize> {
-    use Dir::*;
-    let space = &input.space;
+/// The loop through the start, with the start replaced by the pipe it hides.
+pub struct PipeLoop {
+    start: char,
+    mask: HashMap<(usize, usize), char>,
+    // Positions in walking order, starting at the start tile.
+    path: Vec<(usize, usize)>,
+}
 
-    let mut pos = vec![North, East, South, West]
-        .into_iter()
-        .map(|dir| {
-            space
-                .move_position(&input.start, &dir)
-                .and_then(|next| Some((dir, next)))
-        })
-        .flatten()
-        .filter(|(dir, position)| {
-            space
-                .get(position)
-                .and_then(|tile| tile.redirect(dir).ok())
-                .is_some()
-        });
+impl PipeLoop {
+    /// Pipe inferred under the start tile.
+    pub fn start_tile(&self) -> char {
+        self.start
+    }
 
-    let (mut dir, mut pos) = pos.next().unwrap();
+    /// Number of tiles in the loop, start included.
+    pub fn length(&self) -> usize {
+        self.path.len()
+    }
+}
 
-    let mut steps: usize = 1;
+// Follows the pipes leaving the start towards `dir`. Returns the tiles walked
+// and the direction the walk comes back to the start from, or None if the
+// pipes lead nowhere.
+fn walk(space: &Space, start: (usize, usize), mut dir: Dir) -> Option<(Vec<(usize, usize)>, Dir)> {
+    let mut path = vec![start];
+    let mut pos = space.move_position(&start, &dir)?;
 
-    while let Some(tile) = space.get(&pos) {
+    while path.len() <= space.w * space.h {
+        let tile = space.get(&pos)?;
         if let &Tile('S') = tile {
-            break;
+            return Some((path, dir.flip()));
         }
 
-        dir = tile
-            .redirect(&dir)
-            .context(format! {"At step {steps}"})
-            .unwrap();
-        pos = space.move_position(&pos, &dir).unwrap();
-        steps += 1;
+        path.push(pos);
+        dir = tile.redirect(&dir).ok()?;
+        pos = space.move_position(&pos, &dir)?;
     }
 
-    let furthest = steps.div_ceil(2);
-
-    Ok(furthest)
+    None
 }
 
-#[aoc(day10, part2)]
-pub fn solve_2(input: &Input) -> Result<usize> {
+pub fn find_loop(input: &Input) -> Result<PipeLoop> {
     use Dir::*;
     let space = &input.space;
 
-    let mut connections = vec![North, East, South, West]
+    // Every neighbour with a pipe pointing at the start, not all of them need
+    // to be part of the loop.
+    let candidates: Vec<Dir> = [North, East, South, West]
         .into_iter()
-        .map(|dir| {
+        .filter(|dir| {
             space
-                .move_position(&input.start, &dir)
-                .and_then(|next| Some((dir, next)))
+                .move_position(&input.start, dir)
+                .and_then(|next| space.get(&next))
+                .is_some_and(|tile| tile.redirect(dir).is_ok())
         })
-        .flatten()
-        .filter(|(dir, position)| {
-            space
-                .get(position)
-                .and_then(|tile| tile.redirect(dir).ok())
-                .is_some()
-        });
-
-    //let dirs = pos.collect::<Vec<_>>();
-    let (mut dir, mut pos) = connections.next().unwrap();
-    let (mut dir_other, _) = connections.next().unwrap();
+        .collect();
+    trace!("Start candidates: {candidates:?}");
+
+    // Keep the pair of candidates that closes a loop back to the start.
+    let (path, mut start_directions) = candidates
+        .iter()
+        .find_map(|&dir| {
+            let (path, back) = walk(space, input.start, dir)?;
+            Some((path, [dir, back]))
+        })
+        .context("No loop goes through the start")?;
 
-    let mut start_directions = vec![dir, dir_other];
     start_directions.sort();
-    let replacement = match start_directions[0..2]{
+    let start = match start_directions {
         [East, West] => '-',
         [East, North] => 'L',
         [East, South] => 'F',
@@ -228,27 +340,66 @@ pub fn solve_2(input: &Input) -> Result<usize> {
         [North, South] => '|',
         _ => unreachable!("because its sorted"),
     };
-    println!{"Replacement is: {replacement}"};
+    debug!("Replacement is: {start}");
 
-    let mut mask = HashMap::new();
-    mask.insert(input.start, replacement);
+    let mut mask: HashMap<_, _> = path
+        .iter()
+        .map(|&pos| (pos, space.get(&pos).unwrap().0))
+        .collect();
+    mask.insert(input.start, start);
 
-    while let Some(tile) = space.get(&pos) {
-        if let &Tile('S') = tile {
-            break;
-        }
+    debug!("Loop of {} tiles", path.len());
+    Ok(PipeLoop { start, mask, path })
+}
+
+#[aoc(day10, part1)]
+pub fn solve_1(input: &Input) -> Result<usize> {
+    input.solve_1()
+}
 
-        mask.insert(pos, tile.0);
+/// Ways of counting the tiles enclosed by the loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Intersects a horizontal and a vertical parity scan.
+    Scan,
+    /// Shoelace formula for the loop area plus Pick's theorem.
+    Shoelace,
+    /// Flood fill of the outside on a grid three times larger.
+    FloodFill,
+    /// Runs every other strategy and fails if any of them disagree.
+    CrossCheck,
+}
 
-        dir = tile.redirect(&dir).unwrap();
-        pos = space.move_position(&pos, &dir).unwrap();
+impl Strategy {
+    const ALL: [Strategy; 3] = [Strategy::Scan, Strategy::Shoelace, Strategy::FloodFill];
+
+    fn enclosed(&self, space: &Space, pipe_loop: &PipeLoop) -> Result<usize> {
+        let enclosed = match self {
+            Strategy::Scan => scan(space, pipe_loop),
+            Strategy::Shoelace => shoelace(pipe_loop),
+            Strategy::FloodFill => flood_fill(space, pipe_loop),
+            Strategy::CrossCheck => {
+                let counts = Strategy::ALL
+                    .iter()
+                    .map(|strategy| Ok((*strategy, strategy.enclosed(space, pipe_loop)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                if counts.iter().any(|(_, count)| *count != counts[0].1) {
+                    bail!("Strategies disagree: {counts:?}");
+                }
+                counts[0].1
+            }
+        };
+        debug!("{self:?} found {enclosed} tiles enclosed by the loop");
+        Ok(enclosed)
     }
+}
 
+fn scan(space: &Space, pipe_loop: &PipeLoop) -> usize {
+    let mask = &pipe_loop.mask;
     let mut tiles_vertical = HashSet::new();
     let mut tiles_horizontal = HashSet::new();
 
     for r in 0..space.h {
-        let mut in_vertical = false;
         let mut in_horizontal = false;
         for c in 0..space.w {
             match (in_horizontal, mask.get(&(r, c))) {
@@ -258,8 +409,17 @@ pub fn solve_2(input: &Input) -> Result<usize> {
                 (_, Some('|' | 'J' | 'L')) => in_horizontal = !in_horizontal,
                 _ => (),
             }
-            match (in_vertical, mask.get(&(c, r))) {
-                (true, None) => { tiles_vertical.insert((c, r)); },
+        }
+    }
+
+    // Scanned on its own so spaces that are not square are fully covered.
+    for c in 0..space.w {
+        let mut in_vertical = false;
+        for r in 0..space.h {
+            match (in_vertical, mask.get(&(r, c))) {
+                (true, None) => {
+                    tiles_vertical.insert((r, c));
+                }
                 (_, Some('-' | '7' | 'J')) => in_vertical = !in_vertical,
                 _ => (),
             }
@@ -270,7 +430,85 @@ pub fn solve_2(input: &Input) -> Result<usize> {
         .intersection(&tiles_horizontal)
         .collect::<HashSet<_>>();
 
-    Ok(tiles_inside.len())
+    trace!("Tiles inside: {tiles_inside:?}");
+    tiles_inside.len()
+}
+
+fn shoelace(pipe_loop: &PipeLoop) -> usize {
+    let path = &pipe_loop.path;
+    let twice_area = path
+        .iter()
+        .zip(path.iter().cycle().skip(1))
+        .map(|(&(r1, c1), &(r2, c2))| (r1 * c2) as isize - (r2 * c1) as isize)
+        .sum::<isize>()
+        .unsigned_abs();
+
+    // Pick's theorem: A = I + B/2 - 1, with every loop tile on the boundary.
+    (twice_area + 2 - path.len()) / 2
+}
+
+fn flood_fill(space: &Space, pipe_loop: &PipeLoop) -> usize {
+    let path = &pipe_loop.path;
+    let (h, w) = (space.h * 3, space.w * 3);
+
+    // Every tile becomes a 3x3 block with the pipe drawn through its center,
+    // leaving gaps between pipes that run side by side.
+    let mut blocked = vec![vec![false; w]; h];
+    for (&(r1, c1), &(r2, c2)) in path.iter().zip(path.iter().cycle().skip(1)) {
+        let (dr, dc) = (r2 as isize - r1 as isize, c2 as isize - c1 as isize);
+        for step in 0..=3 {
+            let r = (r1 * 3 + 1) as isize + dr * step;
+            let c = (c1 * 3 + 1) as isize + dc * step;
+            blocked[r as usize][c as usize] = true;
+        }
+    }
+
+    let mut outside = vec![vec![false; w]; h];
+    let mut queue = vec![(0usize, 0usize)];
+    outside[0][0] = true;
+    while let Some((r, c)) = queue.pop() {
+        let neighbours = [
+            (r.wrapping_sub(1), c),
+            (r + 1, c),
+            (r, c.wrapping_sub(1)),
+            (r, c + 1),
+        ];
+        for (r, c) in neighbours {
+            if r < h && c < w && !blocked[r][c] && !outside[r][c] {
+                outside[r][c] = true;
+                queue.push((r, c));
+            }
+        }
+    }
+
+    (0..space.h)
+        .flat_map(|r| (0..space.w).map(move |c| (r * 3 + 1, c * 3 + 1)))
+        .filter(|&(r, c)| !blocked[r][c] && !outside[r][c])
+        .count()
+}
+
+pub fn solve_2_with(input: &Input, strategy: Strategy) -> Result<usize> {
+    input.solve_2_with(strategy)
+}
+
+#[aoc(day10, part2)]
+pub fn solve_2(input: &Input) -> Result<usize> {
+    solve_2_with(input, Strategy::Scan)
+}
+
+#[aoc(day10, part2, Shoelace)]
+pub fn solve_2_shoelace(input: &Input) -> Result<usize> {
+    solve_2_with(input, Strategy::Shoelace)
+}
+
+#[aoc(day10, part2, FloodFill)]
+pub fn solve_2_flood_fill(input: &Input) -> Result<usize> {
+    solve_2_with(input, Strategy::FloodFill)
+}
+
+#[aoc(day10, part2, CrossCheck)]
+pub fn solve_2_cross_check(input: &Input) -> Result<usize> {
+    solve_2_with(input, Strategy::CrossCheck)
 }
 
 #[cfg(test)]
@@ -281,27 +519,106 @@ mod test {
     #[allow(unused_imports)]
     use std::fs::read_to_string;
 
+    use proptest::prelude::*;
+
+    use super::{find_loop, solve_2_with, Input, Strategy};
     use crate::day_10::input_generator;
     const P1_TRAIN_SOLUTION: usize = 8;
     const P2_TRAIN_SOLUTION: usize = 10;
 
-    #[test]
-    fn train_problem_1() -> Result<(), Box<dyn Error>> {
-        use super::solve_1;
-        let input = read_to_string("resources/day_10/train_problem_1.inp")?;
+    #[test_log::test]
+    fn train_problem_2_strategies() -> Result<(), Box<dyn Error>> {
+        let input = read_to_string("resources/day_10/train_problem_2.inp")?;
         let input = input_generator(&input)?;
-        let result = solve_1(&input)?;
-        assert_eq!(result, P1_TRAIN_SOLUTION);
+        for strategy in Strategy::ALL {
+            assert_eq!(solve_2_with(&input, strategy)?, P2_TRAIN_SOLUTION);
+        }
         Ok(())
     }
 
-    #[test]
-    fn train_problem_2() -> Result<(), Box<dyn Error>> {
-        use super::solve_2;
-        let input = read_to_string("resources/day_10/train_problem_2.inp")?;
+    #[test_log::test]
+    fn ambiguous_start() -> Result<(), Box<dyn Error>> {
+        use super::{solve_1, solve_2};
+        let input = read_to_string("resources/day_10/train_ambiguous_start.inp")?;
         let input = input_generator(&input)?;
-        let result = solve_2(&input)?;
-        assert_eq!(result, P2_TRAIN_SOLUTION);
+        assert_eq!(find_loop(&input)?.start_tile(), 'F');
+        assert_eq!(solve_1(&input)?, 4);
+        assert_eq!(solve_2(&input)?, 1);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn input() -> Result<(), Box<dyn Error>> {
+        let input: Input = read_to_string("resources/day_10/train_problem_1.inp")?.parse()?;
+        assert_eq!((input.width(), input.height()), (5, 5));
+        assert_eq!(input.start(), (2, 0));
+        assert_eq!(input.tile((2, 1)), Some('J'));
+        assert_eq!(input.tile((5, 0)), None);
+        assert_eq!(input.find_loop()?.start_tile(), 'F');
+        assert_eq!(input.solve_1()?, P1_TRAIN_SOLUTION);
+
+        let input: Input = read_to_string("resources/day_10/train_problem_2.inp")?.parse()?;
+        assert_eq!(input.solve_2_with(Strategy::Shoelace)?, P2_TRAIN_SOLUTION);
+        assert!("".parse::<Input>().is_err());
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn display_round_trip() -> Result<(), Box<dyn Error>> {
+        for (path, input) in crate::resources::inputs(10)? {
+            let space: Input = input.parse()?;
+            assert_eq!(space.to_string(), input.trim_end(), "{path:?}");
+            let printed: Input = space.to_string().parse()?;
+            assert_eq!(printed.to_string(), space.to_string());
+            assert_eq!(printed.start(), space.start());
+        }
         Ok(())
     }
+
+    proptest! {
+        #[test]
+        fn parity_scan_matches_flood_fill(seed: u64, width in 3usize..30, height in 3usize..30) {
+            use super::solve_2;
+            use crate::generators::{day_10::{generate, Params}, rng};
+
+            let params = Params {
+                width,
+                height,
+                junk: 0.0,
+            };
+            let input = input_generator(&generate(&mut rng(seed), &params)).unwrap();
+            prop_assert_eq!(
+                solve_2(&input).unwrap(),
+                solve_2_with(&input, Strategy::FloodFill).unwrap()
+            );
+        }
+
+        #[test]
+        fn strategies_agree(seed: u64, width in 3usize..30, height in 3usize..30) {
+            use crate::generators::{day_10::{generate, Params}, rng};
+
+            let params = Params {
+                width,
+                height,
+                junk: 0.0,
+            };
+            let input = input_generator(&generate(&mut rng(seed), &params)).unwrap();
+            prop_assert!(solve_2_with(&input, Strategy::CrossCheck).is_ok());
+        }
+
+        #[test]
+        fn start_is_inferred_among_junk(seed: u64, width in 3usize..30, height in 3usize..30) {
+            use crate::generators::{day_10::{generate, Params}, rng};
+
+            let params = Params {
+                width,
+                height,
+                junk: 0.8,
+            };
+            let input = input_generator(&generate(&mut rng(seed), &params)).unwrap();
+            let pipe_loop = find_loop(&input).unwrap();
+            prop_assert!("|-LJ7F".contains(pipe_loop.start_tile()));
+            prop_assert!(solve_2_with(&input, Strategy::CrossCheck).is_ok());
+        }
+    }
 }
@@ -0,0 +1,20 @@
+//! Seedable generators of random but valid puzzle inputs, used to fuzz and
+//! benchmark the solvers beyond the single real input of each day.
+
+use rand::{rngs::StdRng, SeedableRng};
+
+pub mod day_01;
+pub mod day_02;
+pub mod day_03;
+pub mod day_04;
+pub mod day_05;
+pub mod day_06;
+pub mod day_07;
+pub mod day_08;
+pub mod day_09;
+pub mod day_10;
+
+/// Deterministic rng so the same seed always yields the same input.
+pub fn rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
@@ -0,0 +1,72 @@
+use rand::{seq::SliceRandom, Rng};
+
+const SPELLED: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+pub struct Params {
+    pub lines: usize,
+    pub max_length: usize,
+    // Probability of writing a spelled digit instead of a random character.
+    pub spelled: f64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            lines: 1000,
+            max_length: 40,
+            spelled: 0.1,
+        }
+    }
+}
+
+pub fn generate<R: Rng>(rng: &mut R, params: &Params) -> String {
+    let mut input = String::new();
+
+    for _ in 0..params.lines {
+        let length = rng.gen_range(1..=params.max_length.max(1));
+        let mut line = String::with_capacity(length);
+        while line.len() < length {
+            if rng.gen_bool(params.spelled) {
+                line.push_str(SPELLED.choose(rng).unwrap());
+            } else if rng.gen_bool(0.2) {
+                line.push(rng.gen_range('1'..='9'));
+            } else {
+                line.push(rng.gen_range('a'..='z'));
+            }
+        }
+
+        // Both parts require at least one real digit per line.
+        if !line.chars().any(|c| c.is_ascii_digit()) {
+            let at = rng.gen_range(0..=line.len());
+            line.insert(at, rng.gen_range('1'..='9'));
+        }
+
+        input.push_str(&line);
+        input.push('\n');
+    }
+
+    input
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{generate, Params};
+    use crate::{day_01, generators::rng};
+
+    #[test_log::test]
+    fn generated_lines_have_digits() {
+        let params = Params {
+            lines: 50,
+            ..Default::default()
+        };
+        let input = generate(&mut rng(1), &params);
+        assert_eq!(input.lines().count(), 50);
+        assert!(input
+            .lines()
+            .all(|line| line.chars().any(|c| c.is_ascii_digit())));
+        assert!(day_01::problem_2(&input).unwrap() > 0);
+    }
+}
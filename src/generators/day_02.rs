@@ -0,0 +1,55 @@
+use rand::{seq::SliceRandom, Rng};
+
+pub struct Params {
+    pub games: usize,
+    pub max_rounds: usize,
+    pub max_cubes: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            games: 100,
+            max_rounds: 6,
+            max_cubes: 20,
+        }
+    }
+}
+
+pub fn generate<R: Rng>(rng: &mut R, params: &Params) -> String {
+    let mut input = String::new();
+
+    for id in 1..=params.games {
+        let rounds = (0..rng.gen_range(1..=params.max_rounds.max(1)))
+            .map(|_| {
+                let mut colors = vec!["red", "green", "blue"];
+                colors.shuffle(rng);
+                colors.truncate(rng.gen_range(1..=3));
+                colors
+                    .into_iter()
+                    .map(|color| format!("{} {color}", rng.gen_range(1..=params.max_cubes.max(1))))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        input.push_str(&format!("Game {id}: {rounds}\n"));
+    }
+
+    input
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{generate, Params};
+    use crate::{day_02, generators::rng};
+
+    #[test_log::test]
+    fn generated_games_parse() {
+        let input = generate(&mut rng(2), &Params::default());
+        assert_eq!(input, generate(&mut rng(2), &Params::default()));
+        assert!(day_02::problem_2::solve(&input).is_ok());
+    }
+}
@@ -0,0 +1,72 @@
+use rand::{seq::SliceRandom, Rng};
+
+const SYMBOLS: [char; 10] = ['*', '#', '+', '$', '/', '@', '=', '%', '&', '-'];
+
+pub struct Params {
+    pub width: usize,
+    pub height: usize,
+    // Probability of starting a number on a free cell.
+    pub numbers: f64,
+    // Probability of placing a symbol on a free cell.
+    pub symbols: f64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            width: 140,
+            height: 140,
+            numbers: 0.15,
+            symbols: 0.05,
+        }
+    }
+}
+
+pub fn generate<R: Rng>(rng: &mut R, params: &Params) -> String {
+    let mut input = String::new();
+
+    for _ in 0..params.height {
+        let mut row = String::with_capacity(params.width);
+        while row.len() < params.width {
+            let free = params.width - row.len();
+            if rng.gen_bool(params.numbers) {
+                let digits = rng.gen_range(1..=3).min(free);
+                let number =
+                    rng.gen_range(10usize.pow(digits as u32 - 1)..10usize.pow(digits as u32));
+                row.push_str(&number.to_string());
+                // Keep numbers apart so two of them never merge into one.
+                if row.len() < params.width {
+                    row.push('.');
+                }
+            } else if rng.gen_bool(params.symbols) {
+                row.push(*SYMBOLS.choose(rng).unwrap());
+            } else {
+                row.push('.');
+            }
+        }
+        input.push_str(&row);
+        input.push('\n');
+    }
+
+    input
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{generate, Params};
+    use crate::{day_03, generators::rng};
+
+    #[test_log::test]
+    fn generated_schematic_has_requested_size() {
+        let params = Params {
+            width: 30,
+            height: 20,
+            ..Default::default()
+        };
+        let input = generate(&mut rng(3), &params);
+        assert_eq!(input.lines().count(), 20);
+        assert!(input.lines().all(|line| line.len() == 30));
+        assert!(day_03::problem_1::solve(&input).is_ok());
+    }
+}
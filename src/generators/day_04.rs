@@ -0,0 +1,82 @@
+use rand::{
+    seq::{index::sample, SliceRandom},
+    Rng,
+};
+
+pub struct Params {
+    pub cards: usize,
+    pub winning: usize,
+    pub numbers: usize,
+    pub max_number: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            cards: 200,
+            winning: 10,
+            numbers: 25,
+            max_number: 99,
+        }
+    }
+}
+
+fn side(numbers: &[usize]) -> String {
+    numbers
+        .iter()
+        .map(|number| format!("{number:>2}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn generate<R: Rng>(rng: &mut R, params: &Params) -> String {
+    let id_width = params.cards.to_string().len();
+    let pool = params.max_number.max(params.winning).max(params.numbers);
+    let mut input = String::new();
+
+    for id in 1..=params.cards {
+        let winning: Vec<usize> = sample(rng, pool, params.winning)
+            .into_iter()
+            .map(|n| n + 1)
+            .collect();
+
+        // Cards never win copies past the end of the table.
+        let max_wins = (params.cards - id).min(params.winning).min(params.numbers);
+        let wins = rng.gen_range(0..=max_wins);
+
+        let mut numbers: Vec<usize> = winning[..wins].to_vec();
+        let losing: Vec<usize> = (1..=pool).filter(|n| !winning.contains(n)).collect();
+        numbers.extend(
+            sample(rng, losing.len(), (params.numbers - wins).min(losing.len()))
+                .into_iter()
+                .map(|i| losing[i]),
+        );
+        numbers.shuffle(rng);
+
+        input.push_str(&format!(
+            "Card {id:>id_width$}: {} | {}\n",
+            side(&winning),
+            side(&numbers)
+        ));
+    }
+
+    input
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{generate, Params};
+    use crate::{day_04, generators::rng};
+
+    #[test_log::test]
+    fn generated_cards_never_overflow() {
+        let params = Params {
+            cards: 30,
+            ..Default::default()
+        };
+        let input = generate(&mut rng(4), &params);
+        // Would panic indexing past the last card otherwise.
+        assert!(day_04::problem_2::solve(&input).unwrap() >= 30);
+    }
+}
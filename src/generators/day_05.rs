@@ -0,0 +1,144 @@
+use rand::{
+    seq::{index::sample, SliceRandom},
+    Rng,
+};
+
+const CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+
+pub struct Params {
+    // Number of seed values, rounded up to pairs so part 2 can read ranges.
+    pub seeds: usize,
+    pub ranges_per_map: usize,
+    pub max_value: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            seeds: 20,
+            ranges_per_map: 30,
+            max_value: 1 << 32,
+        }
+    }
+}
+
+// Splits [0, max_value) into `count` disjoint ranges with random gaps between
+// them, returned as (start, size) in increasing order.
+fn disjoint_ranges<R: Rng>(rng: &mut R, count: usize, max_value: usize) -> Vec<(usize, usize)> {
+    let count = count.min((max_value - 1) / 2);
+    let mut cuts: Vec<usize> = sample(rng, max_value - 1, 2 * count)
+        .into_iter()
+        .map(|cut| cut + 1)
+        .collect();
+    cuts.sort();
+    cuts.chunks_exact(2)
+        .map(|pair| (pair[0], pair[1] - pair[0]))
+        .collect()
+}
+
+fn map<R: Rng>(rng: &mut R, params: &Params) -> Vec<(usize, usize, usize)> {
+    let sources = disjoint_ranges(rng, params.ranges_per_map, params.max_value);
+
+    // Lay out the same sizes again in shuffled order, so destinations are
+    // also disjoint and the map stays a bijection.
+    let mut sizes: Vec<usize> = sources.iter().map(|&(_, size)| size).collect();
+    sizes.shuffle(rng);
+    let total: usize = sizes.iter().sum();
+    let mut gaps: Vec<usize> = (0..=sizes.len())
+        .map(|_| rng.gen_range(0..=(params.max_value - total) / (sizes.len() + 1)))
+        .collect();
+
+    let mut destinations = Vec::with_capacity(sizes.len());
+    let mut start = gaps.pop().unwrap_or(0);
+    for (size, gap) in sizes.iter().zip(gaps) {
+        destinations.push((start, *size));
+        start += size + gap;
+    }
+    destinations.shuffle(rng);
+
+    let mut map = Vec::with_capacity(sources.len());
+    for (source, size) in sources {
+        let position = destinations
+            .iter()
+            .position(|&(_, dest_size)| dest_size == size)
+            .unwrap();
+        let (destination, _) = destinations.swap_remove(position);
+        map.push((destination, source, size));
+    }
+    map.shuffle(rng);
+    map
+}
+
+pub fn generate<R: Rng>(rng: &mut R, params: &Params) -> String {
+    let seeds = params.seeds + params.seeds % 2;
+    let seeds = (0..seeds / 2)
+        .flat_map(|_| {
+            let start = rng.gen_range(0..params.max_value);
+            let size =
+                rng.gen_range(1..=(params.max_value - start).min(params.max_value / 10).max(1));
+            [start, size]
+        })
+        .map(|seed| seed.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut input = format!("seeds: {seeds}\n\n");
+
+    for categories in CATEGORIES.windows(2) {
+        input.push_str(&format!("{}-to-{} map:\n", categories[0], categories[1]));
+        for (destination, source, size) in map(rng, params) {
+            input.push_str(&format!("{destination} {source} {size}\n"));
+        }
+        input.push('\n');
+    }
+
+    input
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{generate, Params};
+    use crate::generators::rng;
+
+    #[test_log::test]
+    fn generated_maps_are_disjoint() {
+        let params = Params {
+            seeds: 4,
+            ranges_per_map: 5,
+            max_value: 100,
+        };
+        let input = generate(&mut rng(5), &params);
+
+        let maps: Vec<&str> = input
+            .split("\n\n")
+            .skip(1)
+            .filter(|map| !map.is_empty())
+            .collect();
+        assert_eq!(maps.len(), 7);
+
+        for map in maps {
+            let ranges: Vec<Vec<usize>> = map
+                .lines()
+                .skip(1)
+                .map(|line| line.split(' ').map(|n| n.parse().unwrap()).collect())
+                .collect();
+            for side in [0, 1] {
+                let mut spans: Vec<(usize, usize)> =
+                    ranges.iter().map(|r| (r[side], r[side] + r[2])).collect();
+                spans.sort();
+                assert!(spans.windows(2).all(|pair| pair[0].1 <= pair[1].0));
+                assert!(spans.last().unwrap().1 <= 100);
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use rand::Rng;
+
+pub struct Params {
+    pub races: usize,
+    pub max_duration: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            races: 4,
+            max_duration: 100,
+        }
+    }
+}
+
+fn row(label: &str, values: &[usize]) -> String {
+    let values = values
+        .iter()
+        .map(|value| format!(" {value:>3}"))
+        .collect::<String>();
+    format!("{label:<8}{values}\n")
+}
+
+pub fn generate<R: Rng>(rng: &mut R, params: &Params) -> String {
+    let mut durations = Vec::with_capacity(params.races);
+    let mut records = Vec::with_capacity(params.races);
+
+    for _ in 0..params.races {
+        let duration = rng.gen_range(2..=params.max_duration.max(2));
+        // The best run covers (d/2) * (d - d/2), keep the record below it so
+        // every race can be won.
+        let best = (duration / 2) * (duration - duration / 2);
+        durations.push(duration);
+        records.push(rng.gen_range(0..best));
+    }
+
+    let mut input = row("Time:", &durations);
+    input.push_str(&row("Distance:", &records));
+    input
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{generate, Params};
+    use crate::{day_06, generators::rng};
+
+    #[test_log::test]
+    fn generated_races_can_be_won() {
+        let params = Params {
+            races: 6,
+            ..Default::default()
+        };
+        let input = generate(&mut rng(6), &params);
+        let races: day_06::Races = input.parse().unwrap();
+        // No race is lost for good, or the product would be zero.
+        assert!(races.solve_1() > 0);
+        assert!(input.parse::<day_06::Race>().is_ok());
+    }
+}
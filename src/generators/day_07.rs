@@ -0,0 +1,48 @@
+use rand::{seq::SliceRandom, Rng};
+
+const CARDS: [char; 13] = [
+    'A', 'K', 'Q', 'J', 'T', '9', '8', '7', '6', '5', '4', '3', '2',
+];
+
+pub struct Params {
+    pub hands: usize,
+    pub max_bid: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            hands: 1000,
+            max_bid: 1000,
+        }
+    }
+}
+
+pub fn hand<R: Rng>(rng: &mut R) -> String {
+    (0..5).map(|_| *CARDS.choose(rng).unwrap()).collect()
+}
+
+pub fn generate<R: Rng>(rng: &mut R, params: &Params) -> String {
+    let mut input = String::new();
+
+    for _ in 0..params.hands {
+        let bid = rng.gen_range(1..=params.max_bid.max(1));
+        input.push_str(&format!("{} {bid}\n", hand(rng)));
+    }
+
+    input
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{generate, Params};
+    use crate::{day_07, generators::rng};
+
+    #[test_log::test]
+    fn generated_hands_parse() {
+        let input = generate(&mut rng(7), &Params::default());
+        assert_eq!(input.lines().count(), 1000);
+        assert!(day_07::problem_1::solve(&input).is_ok());
+    }
+}
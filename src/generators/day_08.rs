@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+
+use rand::{seq::SliceRandom, Rng};
+
+pub struct Params {
+    pub nodes: usize,
+    pub instructions: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            nodes: 700,
+            instructions: 270,
+        }
+    }
+}
+
+pub struct GhostParams {
+    pub ghosts: usize,
+    pub instructions: usize,
+    // Each ghost loops every `instructions * k` steps with k below this.
+    pub max_multiple: usize,
+}
+
+impl Default for GhostParams {
+    fn default() -> Self {
+        GhostParams {
+            ghosts: 6,
+            instructions: 20,
+            max_multiple: 40,
+        }
+    }
+}
+
+// Distinct node names whose last letter is neither A nor Z, so they never
+// count as ghost starts or ends.
+fn names<R: Rng>(rng: &mut R, count: usize, used: &mut HashSet<String>) -> Vec<String> {
+    assert!(
+        used.len() + count <= 26 * 26 * 24,
+        "Not enough three letter names for {count} more nodes"
+    );
+    let mut names = Vec::with_capacity(count);
+    while names.len() < count {
+        let name: String = [
+            rng.gen_range('A'..='Z'),
+            rng.gen_range('A'..='Z'),
+            rng.gen_range('B'..='Y'),
+        ]
+        .into_iter()
+        .collect();
+        if used.insert(name.clone()) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+fn instructions<R: Rng>(rng: &mut R, count: usize) -> Vec<char> {
+    (0..count.max(1))
+        .map(|_| if rng.gen_bool(0.5) { 'L' } else { 'R' })
+        .collect()
+}
+
+fn render(instructions: &[char], network: &[(String, String, String)]) -> String {
+    let mut input: String = instructions.iter().collect();
+    input.push_str("\n\n");
+    for (from, left, right) in network {
+        input.push_str(&format!("{from} = ({left}, {right})\n"));
+    }
+    input
+}
+
+// A network where following the instructions from AAA reaches ZZZ after
+// visiting every node once.
+pub fn generate<R: Rng>(rng: &mut R, params: &Params) -> String {
+    let instructions = instructions(rng, params.instructions);
+
+    let mut used = HashSet::from(["AAA".to_string(), "ZZZ".to_string()]);
+    let mut path = vec!["AAA".to_string()];
+    path.extend(names(rng, params.nodes.saturating_sub(2), &mut used));
+    path.push("ZZZ".to_string());
+
+    let mut network = Vec::with_capacity(path.len());
+    for (step, from) in path.iter().enumerate() {
+        let next = path.get(step + 1).unwrap_or(from).clone();
+        let other = path.choose(rng).unwrap().clone();
+        let (left, right) = match instructions[step % instructions.len()] {
+            'L' => (next, other),
+            _ => (other, next),
+        };
+        network.push((from.clone(), left, right));
+    }
+    network.shuffle(rng);
+
+    render(&instructions, &network)
+}
+
+// A network for part 2: every ghost walks from its ..A node into a ring whose
+// only ..Z node is hit every ring length steps, so the ghosts meet at the
+// least common multiple of their ring lengths.
+pub fn generate_ghosts<R: Rng>(rng: &mut R, params: &GhostParams) -> String {
+    let instructions = instructions(rng, params.instructions);
+
+    let mut used = HashSet::new();
+    let mut network = Vec::new();
+
+    for ghost in 0..params.ghosts {
+        let prefix: String = [ghost / 26 % 26, ghost % 26]
+            .into_iter()
+            .map(|letter| (b'A' + letter as u8) as char)
+            .collect();
+        let start = format!("{prefix}A");
+        let end = format!("{prefix}Z");
+        used.insert(start.clone());
+        used.insert(end.clone());
+
+        let length = instructions.len() * rng.gen_range(1..=params.max_multiple.max(1));
+        let mut ring = names(rng, length - 1, &mut used);
+        ring.push(end);
+
+        network.push((start, ring[0].clone(), ring[0].clone()));
+        for (i, from) in ring.iter().enumerate() {
+            let next = ring[(i + 1) % ring.len()].clone();
+            network.push((from.clone(), next.clone(), next));
+        }
+    }
+    network.shuffle(rng);
+
+    render(&instructions, &network)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{generate, generate_ghosts, GhostParams, Params};
+    use crate::{day_08, generators::rng};
+
+    #[test_log::test]
+    fn generated_network_reaches_zzz() {
+        let params = Params {
+            nodes: 50,
+            instructions: 7,
+        };
+        let input = generate(&mut rng(8), &params);
+        let data = day_08::input_generator(&input).unwrap();
+        assert_eq!(day_08::solve_1(&data).unwrap(), 49);
+    }
+
+    #[test_log::test]
+    fn generated_ghosts_meet() {
+        let params = GhostParams {
+            ghosts: 3,
+            instructions: 5,
+            max_multiple: 7,
+        };
+        let input = generate_ghosts(&mut rng(8), &params);
+        let data = day_08::input_generator(&input).unwrap();
+        assert_eq!(day_08::solve_2(&data).unwrap() % 5, 0);
+    }
+}
@@ -0,0 +1,60 @@
+use rand::Rng;
+
+pub struct Params {
+    pub histories: usize,
+    pub length: usize,
+    pub max_degree: usize,
+    pub max_coefficient: isize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            histories: 200,
+            length: 21,
+            max_degree: 5,
+            max_coefficient: 10,
+        }
+    }
+}
+
+pub fn generate<R: Rng>(rng: &mut R, params: &Params) -> String {
+    let mut input = String::new();
+
+    for _ in 0..params.histories {
+        // Values of an integer polynomial always reduce to zero differences.
+        let degree = rng.gen_range(0..=params.max_degree);
+        let coefficients: Vec<isize> = (0..=degree)
+            .map(|_| rng.gen_range(-params.max_coefficient..=params.max_coefficient))
+            .collect();
+
+        let history = (0..params.length as isize)
+            .map(|x| {
+                coefficients
+                    .iter()
+                    .rev()
+                    .fold(0, |value, coefficient| value * x + coefficient)
+            })
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        input.push_str(&history);
+        input.push('\n');
+    }
+
+    input
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{generate, Params};
+    use crate::{day_09, generators::rng};
+
+    #[test_log::test]
+    fn generated_histories_extrapolate() {
+        let input = generate(&mut rng(9), &Params::default());
+        assert!(day_09::problem_1::solve(&input).is_ok());
+    }
+}
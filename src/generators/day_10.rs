@@ -0,0 +1,175 @@
+use std::collections::HashSet;
+
+use rand::{seq::SliceRandom, Rng};
+
+const PIPES: [char; 6] = ['|', '-', 'L', 'J', '7', 'F'];
+
+pub struct Params {
+    pub width: usize,
+    pub height: usize,
+    // Probability of a tile outside the loop holding a stray pipe instead of
+    // ground.
+    pub junk: f64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            width: 140,
+            height: 140,
+            junk: 0.5,
+        }
+    }
+}
+
+// Random spanning tree over a rows x cols grid of nodes, as a list of edges.
+fn spanning_tree<R: Rng>(
+    rng: &mut R,
+    rows: usize,
+    cols: usize,
+) -> Vec<((usize, usize), (usize, usize))> {
+    let mut visited = HashSet::from([(0, 0)]);
+    let mut stack = vec![(0, 0)];
+    let mut edges = Vec::new();
+
+    while let Some(&(r, c)) = stack.last() {
+        let mut next = vec![];
+        if r > 0 {
+            next.push((r - 1, c));
+        }
+        if c > 0 {
+            next.push((r, c - 1));
+        }
+        if r + 1 < rows {
+            next.push((r + 1, c));
+        }
+        if c + 1 < cols {
+            next.push((r, c + 1));
+        }
+        next.retain(|node| !visited.contains(node));
+
+        match next.choose(rng) {
+            Some(&node) => {
+                visited.insert(node);
+                edges.push(((r, c), node));
+                stack.push(node);
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+
+    edges
+}
+
+// Builds a maze with a single loop. The loop is the outline of a thickened
+// spanning tree: every node becomes a 2x2 block of cells and every edge a 2x1
+// bridge between blocks. The region is simply connected and never touches
+// itself diagonally, so its outline, drawn through the cell corners, is a
+// simple closed curve. Each corner becomes one tile of the maze.
+pub fn generate<R: Rng>(rng: &mut R, params: &Params) -> String {
+    let rows = (params.height / 3).max(1);
+    let cols = (params.width / 3).max(1);
+    let height = params.height.max(3);
+    let width = params.width.max(3);
+
+    let mut region = HashSet::new();
+    for r in 0..rows {
+        for c in 0..cols {
+            for cell in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+                region.insert((3 * r + cell.0, 3 * c + cell.1));
+            }
+        }
+    }
+    for ((r, c), (nr, nc)) in spanning_tree(rng, rows, cols) {
+        let horizontal = r == nr;
+        let (r, c) = (r.min(nr), c.min(nc));
+        if horizontal {
+            region.insert((3 * r, 3 * c + 2));
+            region.insert((3 * r + 1, 3 * c + 2));
+        } else {
+            region.insert((3 * r + 2, 3 * c));
+            region.insert((3 * r + 2, 3 * c + 1));
+        }
+    }
+
+    // Connections of every corner along the outline, as (north, south, west,
+    // east) flags.
+    let mut corners = vec![vec![[false; 4]; width]; height];
+    for &(r, c) in region.iter() {
+        if r == 0 || !region.contains(&(r - 1, c)) {
+            corners[r][c][3] = true;
+            corners[r][c + 1][2] = true;
+        }
+        if !region.contains(&(r + 1, c)) {
+            corners[r + 1][c][3] = true;
+            corners[r + 1][c + 1][2] = true;
+        }
+        if c == 0 || !region.contains(&(r, c - 1)) {
+            corners[r][c][1] = true;
+            corners[r + 1][c][0] = true;
+        }
+        if !region.contains(&(r, c + 1)) {
+            corners[r][c + 1][1] = true;
+            corners[r + 1][c + 1][0] = true;
+        }
+    }
+
+    let mut tiles = vec![vec!['.'; width]; height];
+    let mut outline = vec![];
+    for (r, row) in corners.iter().enumerate() {
+        for (c, connections) in row.iter().enumerate() {
+            tiles[r][c] = match connections {
+                [true, true, false, false] => '|',
+                [false, false, true, true] => '-',
+                [true, false, false, true] => 'L',
+                [true, false, true, false] => 'J',
+                [false, true, true, false] => '7',
+                [false, true, false, true] => 'F',
+                [false, false, false, false] => {
+                    if rng.gen_bool(params.junk) {
+                        *PIPES.choose(rng).unwrap()
+                    } else {
+                        '.'
+                    }
+                }
+                _ => unreachable!("The outline never touches itself"),
+            };
+            if connections.iter().any(|&connected| connected) {
+                outline.push((r, c));
+            }
+        }
+    }
+
+    let &(r, c) = outline.choose(rng).unwrap();
+    tiles[r][c] = 'S';
+
+    tiles
+        .into_iter()
+        .map(|row| row.into_iter().chain(['\n']).collect::<String>())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{generate, Params};
+    use crate::{day_10, generators::rng};
+
+    #[test_log::test]
+    fn generated_maze_has_single_loop() {
+        for seed in 0..10 {
+            let params = Params {
+                width: 20,
+                height: 15,
+                junk: 0.0,
+            };
+            let input = generate(&mut rng(seed), &params);
+            let loop_length = input.chars().filter(|c| !matches!(c, '.' | '\n')).count();
+
+            let input = day_10::input_generator(&input).unwrap();
+            assert_eq!(day_10::solve_1(&input).unwrap(), loop_length / 2);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Puzzle inputs as JSON, through the model of their day, for tools that would
+//! rather not parse the puzzle format. Every model deserializes back too.
+
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+
+use crate::{day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10};
+
+fn dump<T>(input: &str) -> Result<String>
+where
+    T: FromStr + Serialize,
+    anyhow::Error: From<T::Err>,
+{
+    let model: T = input.parse()?;
+    Ok(serde_json::to_string_pretty(&model)?)
+}
+
+/// Parses `input` into the model of `day` and writes it as JSON.
+pub fn to_json(day: u32, input: &str) -> Result<String> {
+    match day {
+        1 => dump::<day_01::Document>(input),
+        2 => dump::<day_02::Record>(input),
+        3 => dump::<day_03::Schematic>(input),
+        4 => dump::<day_04::Pile>(input),
+        5 => dump::<day_05::Almanac>(input),
+        6 => dump::<day_06::Races>(input),
+        7 => dump::<day_07::Game<day_07::Card1>>(input),
+        8 => dump::<day_08::Data>(input),
+        9 => dump::<day_09::Report>(input),
+        10 => dump::<day_10::Input>(input),
+        _ => bail!("There is no model for day {day}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::error::Error;
+    use std::fs::read_to_string;
+
+    use serde::{de::DeserializeOwned, Serialize};
+
+    use super::to_json;
+    use crate::{day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10};
+
+    // JSON read back into a model writes the same JSON again.
+    fn round_trip<T: Serialize + DeserializeOwned>(
+        day: u32,
+        file: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let input = read_to_string(format!("resources/day_{day:02}/{file}"))?;
+        let json = to_json(day, &input)?;
+        let model: T = serde_json::from_str(&json)?;
+        assert_eq!(serde_json::to_string_pretty(&model)?, json);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn models_round_trip() -> Result<(), Box<dyn Error>> {
+        round_trip::<day_01::Document>(1, "train_problem_2.inp")?;
+        round_trip::<day_02::Record>(2, "train_problem_1.inp")?;
+        round_trip::<day_03::Schematic>(3, "train_problem_1.inp")?;
+        round_trip::<day_04::Pile>(4, "train_problem_1.inp")?;
+        round_trip::<day_05::Almanac>(5, "train_problem_1.inp")?;
+        round_trip::<day_06::Races>(6, "train_problem_1.inp")?;
+        round_trip::<day_07::Game<day_07::Card1>>(7, "train_problem_1.inp")?;
+        round_trip::<day_08::Data>(8, "train_problem_2.inp")?;
+        round_trip::<day_09::Report>(9, "train_problem_1.inp")?;
+        round_trip::<day_10::Input>(10, "train_problem_2.inp")?;
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn invalid_json() {
+        use serde_json::from_str;
+
+        assert!(from_str::<day_07::Hand<day_07::Card1>>(r#"{"cards":"AAAAX","bid":1}"#).is_err());
+        assert!(from_str::<day_07::Hand<day_07::Card1>>(
+            r#"{"cards":["A","A","A","A","X"],"bid":1}"#
+        )
+        .is_err());
+        let network =
+            r#"{"indications":["Left"],"nodes":[{"name":"AAA","left":"ZZZ","right":"AAA"}]}"#;
+        assert!(from_str::<day_08::Data>(network).is_err());
+        let parts = r#"{"width":3,"height":1,"numbers":[{"row":0,"start":1,"end":3,"value":467}],"symbols":[]}"#;
+        assert!(from_str::<day_03::Schematic>(parts).is_err());
+        assert!(from_str::<day_10::Input>(r#"{"start":[0,1],"rows":["S-7","L-J"]}"#).is_err());
+        assert!(to_json(11, "").is_err());
+    }
+}
@@ -1,6 +1,13 @@
+//! Advent of Code 2023. Every `day_XX` module documents a model type that
+//! parses with [`FromStr`](std::str::FromStr), prints back to the puzzle
+//! format with [`Display`](std::fmt::Display) and solves both parts as
+//! methods; that, with the `problem_N` functions wrapping it, is the public
+//! surface. Anything not `pub` is free to change between releases.
+
 #[macro_use]
 extern crate aoc_runner_derive;
 
+pub mod client;
 pub mod day_01;
 pub mod day_02;
 pub mod day_03;
@@ -11,5 +18,19 @@ pub mod day_07;
 pub mod day_08;
 pub mod day_09;
 pub mod day_10;
+pub mod generators;
+#[cfg(feature = "serde")]
+pub mod json;
+#[cfg(test)]
+mod manifest;
+pub mod parsers;
+#[cfg(feature = "alloc_profile")]
+pub mod profile;
+#[cfg(test)]
+mod resources;
+pub mod scaffold;
+pub mod solutions;
+pub mod store;
+pub mod streaming;
 
 aoc_lib! { year = 2023 }
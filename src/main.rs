@@ -0,0 +1,188 @@
+//! Tools around the puzzles that `cargo aoc` doesn't cover.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use aoc23::{
+    client::{
+        self,
+        submit::{Answer, Submission},
+        Client,
+    },
+    scaffold, solutions,
+    store::{self, Key},
+};
+use clap::{Parser, Subcommand};
+
+#[derive(Parser)]
+#[command(about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prints an input parsed into the model of its day as JSON.
+    Json {
+        day: u32,
+        /// Defaults to the input `cargo aoc` downloaded for the day.
+        input: Option<PathBuf>,
+    },
+    /// Downloads the input and the puzzle page of a day into the input store,
+    /// then prints the examples of the page.
+    Fetch { day: u32 },
+    /// Submits the answer to a part, unless earlier verdicts already rule
+    /// on it.
+    Submit {
+        day: u32,
+        part: u32,
+        /// Defaults to solving the input `cargo aoc` downloaded for the day.
+        answer: Option<String>,
+    },
+    /// Adds the module, resources, manifest lines and `lib.rs` line of a new day.
+    NewDay {
+        day: u32,
+        /// Puzzle title for the module doc.
+        #[arg(long, default_value = "TODO")]
+        title: String,
+    },
+    /// Manages the encryption of the personal inputs.
+    Inputs {
+        #[command(subcommand)]
+        command: Inputs,
+    },
+    /// Solves a day counting the allocations of each part.
+    Profile {
+        day: u32,
+        /// Only this part instead of both.
+        #[arg(long)]
+        part: Option<u32>,
+        /// Defaults to the input `cargo aoc` downloaded for the day.
+        input: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum Inputs {
+    /// Prints a new key, to keep in the key file or variable.
+    Key,
+    /// Replaces every personal input not encrypted yet by its encrypted
+    /// version.
+    Encrypt,
+    /// Writes a plain copy of every encrypted input, ignored by git, for
+    /// tools like `cargo aoc`.
+    Decrypt,
+}
+
+// Where `cargo aoc` keeps the input of a day.
+fn input_path(day: u32) -> PathBuf {
+    PathBuf::from(format!("input/2023/day{day}.txt"))
+}
+
+fn read_input(day: u32, input: Option<PathBuf>) -> Result<String> {
+    let path = input.unwrap_or_else(|| input_path(day));
+    store::read(&path)
+}
+
+#[cfg(feature = "serde")]
+fn json(day: u32, input: &str) -> Result<String> {
+    aoc23::json::to_json(day, input)
+}
+
+#[cfg(not(feature = "serde"))]
+fn json(_day: u32, _input: &str) -> Result<String> {
+    anyhow::bail!("JSON support needs the serde feature")
+}
+
+#[cfg(feature = "alloc_profile")]
+fn profile(day: u32, part: u32, input: &str) -> Result<String> {
+    let Some(solver) = aoc23::solutions::solver(day, part) else {
+        return Ok(format!("Day {day} part {part}: not solved yet"));
+    };
+    let (answer, stats) = aoc23::profile::profile(solver, input);
+    Ok(format!("Day {day} part {part}: {} ({stats})", answer?))
+}
+
+#[cfg(not(feature = "alloc_profile"))]
+fn profile(_day: u32, _part: u32, _input: &str) -> Result<String> {
+    anyhow::bail!("Allocation profiling needs the alloc_profile feature")
+}
+
+fn inputs(command: Inputs) -> Result<()> {
+    if let Inputs::Key = command {
+        println!("{}", Key::generate());
+        return Ok(());
+    }
+    let key = Key::load()?
+        .with_context(|| format!("No key in ${} or {}", store::KEY_VAR, store::KEY_FILE))?;
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    for path in store::personal_inputs(root)? {
+        match command {
+            Inputs::Encrypt if !store::encrypted(&path).exists() => {
+                let written = store::encrypt_file(&path, &key)?;
+                println!("Encrypted {}", written.display());
+            }
+            Inputs::Decrypt if !path.exists() => {
+                store::decrypt_file(&path, &key)?;
+                println!("Decrypted {}", path.display());
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    env_logger::init();
+
+    match Cli::parse().command {
+        Command::Json { day, input } => {
+            let input = read_input(day, input)?;
+            println!("{}", json(day, &input)?);
+        }
+        Command::Fetch { day } => {
+            let options = client::Options {
+                key: Key::load()?,
+                ..Default::default()
+            };
+            let client = Client::new(client::session()?, options);
+            client.input(day)?;
+            println!("Input of day {day} is in the store");
+            for (i, example) in client.examples(day)?.iter().enumerate() {
+                println!("\nExample {}:\n{example}", i + 1);
+            }
+        }
+        Command::Submit { day, part, answer } => {
+            let answer: Answer = match answer {
+                Some(answer) => answer.parse()?,
+                None => {
+                    let solver = solutions::solver(day, part)
+                        .with_context(|| format!("Day {day} part {part} is not solved yet"))?;
+                    solver(&read_input(day, None)?)?.parse()?
+                }
+            };
+            let client = Client::new(client::session()?, Default::default());
+            match client.submit(day, part, &answer)? {
+                Submission::Sent(verdict) => println!("{answer}: {verdict}"),
+                Submission::Known(verdict) => println!("{answer}: {verdict}, as already told"),
+            }
+        }
+        Command::NewDay { day, title } => {
+            let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+            for path in scaffold::new_day(root, day, &title)? {
+                println!("Wrote {}", path.display());
+            }
+            println!("Add the arms of day {day} to src/solutions.rs for its manifest tests to run");
+        }
+        Command::Inputs { command } => inputs(command)?,
+        Command::Profile { day, part, input } => {
+            let input = read_input(day, input)?;
+            let parts = part.map_or(vec![1, 2], |part| vec![part]);
+            for part in parts {
+                println!("{}", profile(day, part, &input)?);
+            }
+        }
+    }
+    Ok(())
+}
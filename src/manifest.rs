@@ -0,0 +1,22 @@
+//! Tests generated by `build.rs`, one for every line of
+//! `resources/manifest.txt`, checking a part of a day against a puzzle input.
+//! Encrypted inputs are ignored when there is no key.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::{solutions::solver, store::read};
+
+fn check(day: u32, part: u32, input: &str, expected: &str) -> Result<()> {
+    let solve =
+        solver(day, part).with_context(|| format!("Day {day} part {part} has no solver"))?;
+
+    let path = format!("resources/day_{day:02}/{input}");
+    let input = read(Path::new(&path))?;
+    let answer = solve(&input)?;
+    assert_eq!(answer, expected, "day {day} part {part} on {path}");
+    Ok(())
+}
+
+include!(concat!(env!("OUT_DIR"), "/manifest_tests.rs"));
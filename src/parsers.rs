@@ -0,0 +1,318 @@
+//! Nom combinators for the shapes puzzle inputs keep coming in: lists of
+//! numbers, `Label:` and `Card 1:` prefixes, `key = (a, b)` pairs, line and
+//! section lists and character grids. [`parse`] runs one over a whole input
+//! and turns a failure into an error saying where it happened.
+
+use std::{fmt, str::FromStr};
+
+use anyhow::anyhow;
+use nom::{
+    bytes::complete::tag,
+    character::complete::{char, digit1, line_ending, multispace0, none_of, space0, space1},
+    combinator::{all_consuming, opt, recognize},
+    error::{ErrorKind, FromExternalError, ParseError},
+    multi::{many1, separated_list1},
+    sequence::{pair, terminated, tuple},
+    Finish,
+};
+
+pub type IResult<'a, T> = nom::IResult<&'a str, T, Error<'a>>;
+
+/// Where parsing stopped and why. Unlike nom's own errors it keeps the
+/// errors of conversions, such as an unknown card, to report them as is.
+#[derive(Debug)]
+pub struct Error<'a> {
+    input: &'a str,
+    cause: Cause,
+}
+
+#[derive(Debug)]
+enum Cause {
+    Syntax,
+    External(anyhow::Error),
+}
+
+impl<'a> ParseError<&'a str> for Error<'a> {
+    fn from_error_kind(input: &'a str, _: ErrorKind) -> Self {
+        Error {
+            input,
+            cause: Cause::Syntax,
+        }
+    }
+
+    fn append(_: &'a str, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    // The alternative that got further says the most about what went wrong.
+    fn or(self, other: Self) -> Self {
+        if other.input.len() < self.input.len() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl<'a, E: Into<anyhow::Error>> FromExternalError<&'a str, E> for Error<'a> {
+    fn from_external_error(input: &'a str, _: ErrorKind, e: E) -> Self {
+        Error {
+            input,
+            cause: Cause::External(e.into()),
+        }
+    }
+}
+
+impl Error<'_> {
+    // Explains the error as found parsing `whole`.
+    fn explain(self, whole: &str) -> anyhow::Error {
+        match self.cause {
+            Cause::External(error) => error,
+            Cause::Syntax => {
+                let rest = self.input.trim_start_matches([' ', '\t']);
+                anyhow!(
+                    "Unexpected {} at {}",
+                    Unexpected(rest),
+                    Position::of(whole, rest)
+                )
+            }
+        }
+    }
+}
+
+// What comes next where parsing failed: the word there, if any.
+struct Unexpected<'a>(&'a str);
+
+impl fmt::Display for Unexpected<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let word = self.0.split_whitespace().next().unwrap_or("");
+        if self.0.is_empty() {
+            write!(f, "end of input")
+        } else if !self.0.starts_with(word) || word.is_empty() {
+            write!(f, "end of line")
+        } else {
+            write!(f, "{word:?}")
+        }
+    }
+}
+
+struct Position {
+    line: Option<usize>,
+    column: usize,
+}
+
+impl Position {
+    // Where `rest`, a suffix of `whole`, starts. Inputs of a single line only
+    // get a column, as they are usually a line of a larger one.
+    fn of(whole: &str, rest: &str) -> Position {
+        let done = &whole[..whole.len() - rest.len()];
+        let line = whole
+            .trim_end()
+            .contains('\n')
+            .then(|| done.matches('\n').count() + 1);
+        let column = done.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+        Position { line, column }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {line}, ")?;
+        }
+        write!(f, "column {}", self.column)
+    }
+}
+
+/// Runs `parser` over the whole of `input`, trailing whitespace aside.
+pub fn parse<'a, T>(
+    input: &'a str,
+    parser: impl FnMut(&'a str) -> IResult<'a, T>,
+) -> anyhow::Result<T> {
+    all_consuming(terminated(parser, multispace0))(input)
+        .finish()
+        .map(|(_, value)| value)
+        .map_err(|error| error.explain(input))
+}
+
+/// What `parser` recognized, turned into a value by `f`. Unlike with
+/// [`map_res`](nom::combinator::map_res), a value `f` rejects is an error
+/// even inside a list, rather than where the list ends.
+pub fn convert<'a, O, T, E: Into<anyhow::Error>>(
+    mut parser: impl FnMut(&'a str) -> IResult<'a, O>,
+    mut f: impl FnMut(O) -> Result<T, E>,
+) -> impl FnMut(&'a str) -> IResult<'a, T> {
+    move |input| {
+        let (rest, recognized) = parser(input)?;
+        match f(recognized) {
+            Ok(value) => Ok((rest, value)),
+            Err(error) => Err(nom::Err::Failure(Error::from_external_error(
+                input,
+                ErrorKind::MapRes,
+                error,
+            ))),
+        }
+    }
+}
+
+/// A number, negative if it starts with `-`.
+pub fn number<T: FromStr>(input: &str) -> IResult<'_, T> {
+    convert(recognize(pair(opt(char('-')), digit1)), |number: &str| {
+        number
+            .parse()
+            .map_err(|_| anyhow!("Invalid number {number:?}"))
+    })(input)
+}
+
+/// Numbers separated by spaces, as many as there are.
+pub fn numbers<T: FromStr>(input: &str) -> IResult<'_, Vec<T>> {
+    separated_list1(space1, number)(input)
+}
+
+/// `name:` and the spaces after it.
+pub fn label<'a>(name: &'static str) -> impl FnMut(&'a str) -> IResult<'a, &'a str> {
+    terminated(tag(name), pair(char(':'), space0))
+}
+
+/// `name N:` and the spaces after it, giving `N`.
+pub fn header<'a, T: FromStr>(name: &'static str) -> impl FnMut(&'a str) -> IResult<'a, T> {
+    move |input| {
+        let (input, (_, _, n, _, _)) =
+            tuple((tag(name), space1, number, char(':'), space0))(input)?;
+        Ok((input, n))
+    }
+}
+
+/// `key = (left, right)`.
+pub fn key_pair<'a, K, V>(
+    mut key: impl FnMut(&'a str) -> IResult<'a, K>,
+    mut value: impl FnMut(&'a str) -> IResult<'a, V>,
+) -> impl FnMut(&'a str) -> IResult<'a, (K, (V, V))> {
+    move |input| {
+        let (input, k) = key(input)?;
+        let (input, _) = tuple((space0, char('='), space0, char('('), space0))(input)?;
+        let (input, left) = value(input)?;
+        let (input, _) = tuple((space0, char(','), space0))(input)?;
+        let (input, right) = value(input)?;
+        let (input, _) = pair(space0, char(')'))(input)?;
+        Ok((input, (k, (left, right))))
+    }
+}
+
+/// A blank line between two sections.
+pub fn blank_line(input: &str) -> IResult<'_, &str> {
+    recognize(pair(line_ending, many1(line_ending)))(input)
+}
+
+/// One `item` per line, up to a blank line or the end of the input. A line
+/// that is there has to be an `item`, so errors point at the line itself.
+pub fn lines<'a, T>(
+    item: impl FnMut(&'a str) -> IResult<'a, T>,
+) -> impl FnMut(&'a str) -> IResult<'a, Vec<T>> {
+    separated(item, line_ending)
+}
+
+/// Sections separated by blank lines, up to the end of the input.
+pub fn sections<'a, T>(
+    section: impl FnMut(&'a str) -> IResult<'a, T>,
+) -> impl FnMut(&'a str) -> IResult<'a, Vec<T>> {
+    separated(section, blank_line)
+}
+
+// Items after `separator` as long as something other than a line ending
+// follows it.
+fn separated<'a, T, S>(
+    mut item: impl FnMut(&'a str) -> IResult<'a, T>,
+    mut separator: impl FnMut(&'a str) -> IResult<'a, S>,
+) -> impl FnMut(&'a str) -> IResult<'a, Vec<T>> {
+    move |mut input| {
+        let mut items = Vec::new();
+        loop {
+            let (rest, value) = item(input)?;
+            items.push(value);
+            match separator(rest) {
+                Ok((next, _)) if !next.is_empty() && line_ending::<_, Error>(next).is_err() => {
+                    input = next
+                }
+                _ => return Ok((rest, items)),
+            }
+        }
+    }
+}
+
+/// Rows of characters, each turned into a cell by `cell`. Rows may differ in
+/// length.
+pub fn grid<'a, T, E: Into<anyhow::Error>>(
+    cell: impl Fn(char) -> Result<T, E>,
+) -> impl FnMut(&'a str) -> IResult<'a, Vec<Vec<T>>> {
+    lines(many1(convert(none_of("\r\n"), cell)))
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::{convert::Infallible, error::Error};
+
+    use anyhow::bail;
+    use nom::character::complete::alphanumeric1;
+
+    use super::{grid, header, key_pair, label, lines, number, numbers, parse, sections};
+
+    #[test_log::test]
+    fn numbers_and_labels() -> Result<(), Box<dyn Error>> {
+        assert_eq!(parse("-12", number::<isize>)?, -12);
+        assert_eq!(parse("1 2  3\n", numbers::<u8>)?, vec![1, 2, 3]);
+        let time = |input| {
+            let (input, _) = label("Time")(input)?;
+            numbers::<usize>(input)
+        };
+        assert_eq!(parse("Time:      7  15   30", time)?, vec![7, 15, 30]);
+        assert_eq!(parse("Card  12: ", header::<usize>("Card"))?, 12);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn pairs() -> Result<(), Box<dyn Error>> {
+        assert_eq!(
+            parse("AAA = (BBB, CCC)", key_pair(alphanumeric1, alphanumeric1))?,
+            ("AAA", ("BBB", "CCC"))
+        );
+        assert!(parse("AAA = (BBB)", key_pair(alphanumeric1, alphanumeric1)).is_err());
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn lines_and_sections() -> Result<(), Box<dyn Error>> {
+        let input = "1 2\n3\n\n\n4 5 6\n";
+        assert_eq!(
+            parse(input, sections(lines(numbers::<u8>)))?,
+            vec![vec![vec![1, 2], vec![3]], vec![vec![4, 5, 6]]]
+        );
+        assert!(parse(input, lines(numbers::<u8>)).is_err());
+
+        let cells = parse("#.\n.#.\n", grid(|c| Ok::<_, Infallible>(c == '#')))?;
+        assert_eq!(cells, vec![vec![true, false], vec![false, true, false]]);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn errors() {
+        let error = parse("1 2 x", numbers::<u8>).unwrap_err();
+        assert_eq!(error.to_string(), r#"Unexpected "x" at column 5"#);
+        let error = parse("1 2\n3 x 4\n", lines(numbers::<u8>)).unwrap_err();
+        assert_eq!(error.to_string(), r#"Unexpected "x" at line 2, column 3"#);
+        let error = parse("1 2\n\n3", lines(numbers::<u8>)).unwrap_err();
+        assert_eq!(error.to_string(), r#"Unexpected "3" at line 3, column 1"#);
+        let error = parse("Card 1", header::<u8>("Card")).unwrap_err();
+        assert_eq!(error.to_string(), "Unexpected end of input at column 7");
+        let error = parse("1 300", numbers::<u8>).unwrap_err();
+        assert_eq!(error.to_string(), r#"Invalid number "300""#);
+
+        let cell = |c| match c {
+            '.' | '#' => Ok(c),
+            _ => bail!("Unknown cell {c}"),
+        };
+        let error = parse("..\n.?", grid(cell)).unwrap_err();
+        assert_eq!(error.to_string(), "Unknown cell ?");
+    }
+}
@@ -0,0 +1,191 @@
+//! Allocation profiling of the solvers. Building with the `alloc_profile`
+//! feature installs a counting global allocator; [`measure`] then reports
+//! what a closure allocated. Counters are per thread, so other threads
+//! (parallel tests, say) never leak into a measurement.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    fmt,
+};
+
+use anyhow::Result;
+
+use crate::solutions::Solver;
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+struct Counting;
+
+#[derive(Clone, Copy)]
+struct Counters {
+    allocations: usize,
+    bytes: usize,
+    // Live bytes since the measurement started; negative when the closure
+    // frees memory allocated before it.
+    current: isize,
+    peak: isize,
+}
+
+impl Counters {
+    const ZERO: Counters = Counters {
+        allocations: 0,
+        bytes: 0,
+        current: 0,
+        peak: 0,
+    };
+}
+
+// Const initialized and without a destructor, so reading it never allocates
+// and it stays usable while the thread shuts down.
+thread_local! {
+    static COUNTERS: Cell<Counters> = const { Cell::new(Counters::ZERO) };
+}
+
+fn record(requested: usize, grown: isize) {
+    let _ = COUNTERS.try_with(|counters| {
+        let mut c = counters.get();
+        if requested > 0 {
+            c.allocations += 1;
+            c.bytes += requested;
+        }
+        c.current += grown;
+        c.peak = c.peak.max(c.current);
+        counters.set(c);
+    });
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record(layout.size(), layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record(layout.size(), layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record(0, -(layout.size() as isize));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            record(new_size, new_size as isize - layout.size() as isize);
+        }
+        new
+    }
+}
+
+/// What a closure allocated on its thread.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    /// Allocations and reallocations made.
+    pub allocations: usize,
+    /// Bytes requested over all of them; a `Vec` growing from 4 to 8 bytes
+    /// counts 12.
+    pub bytes: usize,
+    /// Most bytes live at once, beyond those live when it started.
+    pub peak: usize,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} allocations, {} bytes, peak {} bytes",
+            self.allocations, self.bytes, self.peak
+        )
+    }
+}
+
+/// Runs `f`, counting its allocations. Measurements nest: the outer one
+/// includes whatever the inner one counted.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Stats) {
+    let outer = COUNTERS.with(|counters| counters.replace(Counters::ZERO));
+    let result = f();
+    let inner = COUNTERS.with(|counters| {
+        let inner = counters.get();
+        counters.set(Counters {
+            allocations: outer.allocations + inner.allocations,
+            bytes: outer.bytes + inner.bytes,
+            current: outer.current + inner.current,
+            peak: outer.peak.max(outer.current + inner.peak),
+        });
+        inner
+    });
+    let stats = Stats {
+        allocations: inner.allocations,
+        bytes: inner.bytes,
+        peak: inner.peak.max(0) as usize,
+    };
+    (result, stats)
+}
+
+/// Solves `input` with `solver`, counting allocations from parsing to answer.
+pub fn profile(solver: Solver, input: &str) -> (Result<String>, Stats) {
+    measure(|| solver(input))
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::error::Error;
+    use std::fs::read_to_string;
+
+    use super::{measure, profile, Stats};
+    use crate::solutions::solver;
+
+    #[test_log::test]
+    fn counts_a_vec() {
+        let (_, stats) = measure(|| Vec::<u8>::with_capacity(1024));
+        assert_eq!(
+            stats,
+            Stats {
+                allocations: 1,
+                bytes: 1024,
+                peak: 1024
+            }
+        );
+    }
+
+    #[test_log::test]
+    fn freeing_older_memory() {
+        let old = vec![0u8; 1024];
+        let (_, stats) = measure(|| drop(old));
+        assert_eq!(stats, Stats::default());
+    }
+
+    #[test_log::test]
+    fn nested() {
+        let ((_, inner), outer) = measure(|| {
+            let _first = Vec::<u8>::with_capacity(100);
+            measure(|| Vec::<u8>::with_capacity(200))
+        });
+        assert_eq!(inner.allocations, 1);
+        assert_eq!(inner.peak, 200);
+        assert_eq!(outer.allocations, 2);
+        assert_eq!(outer.bytes, 300);
+        assert_eq!(outer.peak, 300);
+    }
+
+    #[test_log::test]
+    fn profiles_a_solver() -> Result<(), Box<dyn Error>> {
+        let input = read_to_string("resources/day_01/train_problem_1.inp")?;
+        let (answer, stats) = profile(solver(1, 1).unwrap(), &input);
+        assert_eq!(answer?, "142");
+        assert!(stats.allocations > 0);
+        assert!(stats.peak <= stats.bytes);
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+//! The puzzle inputs kept under `resources/` for tests. Personal inputs are
+//! encrypted (see [`crate::store`]); without the key they are left out of
+//! [`inputs`]. Tests that need one in particular read it with
+//! [`store::read`] and are ignored unless built with the `input_key` cfg.
+
+use std::{
+    fs::read_dir,
+    io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use log::warn;
+
+use crate::store::{self, MissingKey};
+
+/// Every input kept for `day`, by file name, along with its path.
+pub fn inputs(day: u32) -> Result<Vec<(PathBuf, String)>> {
+    let paths = read_dir(format!("resources/day_{day:02}"))?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    // Encrypted inputs go by the name they are read with.
+    let mut paths: Vec<_> = paths
+        .into_iter()
+        .map(|path| match path.extension() {
+            Some(extension) if extension == "enc" => path.with_extension(""),
+            _ => path,
+        })
+        .filter(|path| path.extension().is_some_and(|extension| extension == "inp"))
+        .collect();
+    paths.sort();
+    paths.dedup();
+
+    let mut inputs = Vec::new();
+    for path in paths {
+        if let Some(input) = read(&path)? {
+            inputs.push((path, input));
+        }
+    }
+    Ok(inputs)
+}
+
+/// The input at `path`, or `None` when it is encrypted and there is no key.
+pub fn read(path: impl AsRef<Path>) -> Result<Option<String>> {
+    match store::read(path.as_ref()) {
+        Ok(input) => Ok(Some(input)),
+        Err(error) if error.is::<MissingKey>() => {
+            warn!("Skipping: {error}");
+            Ok(None)
+        }
+        Err(error) => Err(error),
+    }
+}
@@ -0,0 +1,176 @@
+//! Scaffolding for a new day: the module with its model, solvers and tests,
+//! the resources it reads, their lines in the test manifest, and its line in
+//! `lib.rs`. The arms of `solutions.rs` are left to be written by hand.
+
+use std::{
+    fs::{self, read_to_string},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+
+const TEMPLATE: &str = include_str!("scaffold/day.rs.in");
+
+/// Inputs the tests of a new day read, left empty until the puzzle is out,
+/// with the part each is for.
+const RESOURCES: [(&str, u32); 4] = [
+    ("train_problem_1.inp", 1),
+    ("train_problem_2.inp", 2),
+    ("problem_1.inp", 1),
+    ("problem_2.inp", 2),
+];
+
+/// The module of `day` as first written.
+pub fn module(day: u32, title: &str) -> String {
+    TEMPLATE
+        .replace("{{day}}", &day.to_string())
+        .replace("{{dd}}", &format!("{day:02}"))
+        .replace("{{title}}", title)
+}
+
+// `lib.rs` with `pub mod day_XX;` among the other days, in order.
+fn register(lib: &str, day: u32) -> Result<String> {
+    let module = format!("pub mod day_{day:02};");
+    let mut lines: Vec<&str> = lib.lines().collect();
+    if lines.contains(&module.as_str()) {
+        bail!("Day {day} is already in lib.rs");
+    }
+    let mut days = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.starts_with("pub mod day_"));
+    let at = match days.clone().find(|(_, line)| **line > module.as_str()) {
+        Some((i, _)) => i,
+        None => match days.next_back() {
+            Some((i, _)) => i + 1,
+            None => bail!("lib.rs has no day to add day {day} after"),
+        },
+    };
+    lines.insert(at, &module);
+    Ok(lines.join("\n") + "\n")
+}
+
+// `manifest` with the inputs of `day` at the end, their answers unknown.
+fn list(manifest: &str, day: u32) -> String {
+    let mut manifest = manifest.trim_end().to_string() + "\n\n";
+    let mut resources = RESOURCES;
+    resources.sort_by_key(|&(_, part)| part);
+    for (resource, part) in resources {
+        manifest += &format!("{day} {part} {resource} ?\n");
+    }
+    manifest
+}
+
+/// Adds `day` to the crate rooted at `root`, returning the files it wrote.
+/// Nothing is overwritten: an existing module or resource is an error.
+pub fn new_day(root: &Path, day: u32, title: &str) -> Result<Vec<PathBuf>> {
+    let source = root.join(format!("src/day_{day:02}.rs"));
+    let resources = root.join(format!("resources/day_{day:02}"));
+    if source.exists() {
+        bail!("{} already exists", source.display());
+    }
+    if resources.exists() {
+        bail!("{} already exists", resources.display());
+    }
+
+    let lib_path = root.join("src/lib.rs");
+    let lib =
+        read_to_string(&lib_path).with_context(|| format!("Reading {}", lib_path.display()))?;
+    let lib = register(&lib, day)?;
+    let manifest_path = root.join("resources/manifest.txt");
+    let manifest = read_to_string(&manifest_path)
+        .with_context(|| format!("Reading {}", manifest_path.display()))?;
+
+    fs::write(&source, module(day, title))
+        .with_context(|| format!("Writing {}", source.display()))?;
+    let mut written = vec![source];
+    fs::create_dir_all(&resources).with_context(|| format!("Creating {}", resources.display()))?;
+    for (resource, _) in RESOURCES {
+        let path = resources.join(resource);
+        fs::write(&path, "").with_context(|| format!("Writing {}", path.display()))?;
+        written.push(path);
+    }
+    fs::write(&manifest_path, list(&manifest, day))
+        .with_context(|| format!("Writing {}", manifest_path.display()))?;
+    written.push(manifest_path);
+    fs::write(&lib_path, lib).with_context(|| format!("Writing {}", lib_path.display()))?;
+    written.push(lib_path);
+    Ok(written)
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::error::Error;
+    use std::fs::{self, read_to_string};
+
+    use super::{list, module, new_day, register};
+
+    const LIB: &str = "pub mod day_01;\npub mod day_03;\npub mod generators;\n";
+    const MANIFEST: &str = "# day part input expected\n\n1 1 problem_1.inp 53386\n";
+
+    #[test_log::test]
+    fn registered_in_order() -> Result<(), Box<dyn Error>> {
+        assert_eq!(
+            register(LIB, 2)?,
+            "pub mod day_01;\npub mod day_02;\npub mod day_03;\npub mod generators;\n"
+        );
+        assert_eq!(
+            register(LIB, 11)?,
+            "pub mod day_01;\npub mod day_03;\npub mod day_11;\npub mod generators;\n"
+        );
+        assert_eq!(
+            register(LIB, 3).unwrap_err().to_string(),
+            "Day 3 is already in lib.rs"
+        );
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn listed_in_manifest() {
+        assert_eq!(
+            list(MANIFEST, 11),
+            "# day part input expected\n\n1 1 problem_1.inp 53386\n\n\
+             11 1 train_problem_1.inp ?\n11 1 problem_1.inp ?\n\
+             11 2 train_problem_2.inp ?\n11 2 problem_2.inp ?\n"
+        );
+    }
+
+    #[test_log::test]
+    fn scaffold() -> Result<(), Box<dyn Error>> {
+        let root = tempfile::tempdir()?;
+        fs::create_dir_all(root.path().join("src"))?;
+        fs::create_dir_all(root.path().join("resources"))?;
+        fs::write(root.path().join("src/lib.rs"), LIB)?;
+        fs::write(root.path().join("resources/manifest.txt"), MANIFEST)?;
+
+        let written = new_day(root.path(), 11, "Cosmic Expansion")?;
+        assert_eq!(written.len(), 7);
+        assert_eq!(
+            read_to_string(root.path().join("src/day_11.rs"))?,
+            module(11, "Cosmic Expansion")
+        );
+        assert_eq!(
+            read_to_string(root.path().join("resources/day_11/train_problem_1.inp"))?,
+            ""
+        );
+        assert!(read_to_string(root.path().join("src/lib.rs"))?.contains("pub mod day_11;\n"));
+        assert_eq!(
+            read_to_string(root.path().join("resources/manifest.txt"))?,
+            list(MANIFEST, 11)
+        );
+
+        let error = new_day(root.path(), 11, "Again").unwrap_err();
+        assert!(error.to_string().ends_with("day_11.rs already exists"));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn template() {
+        let module = module(7, "Camel Cards");
+        assert!(module.starts_with("//! Day 7: Camel Cards."));
+        assert!(module.contains("#[aoc(day7, part2)]"));
+        assert!(module.contains("\"resources/day_07/train_problem_1.inp\""));
+        assert!(!module.contains("{{"));
+    }
+}
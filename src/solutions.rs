@@ -0,0 +1,43 @@
+//! Every day's solvers behind a single signature, for tools that only know
+//! which puzzle to solve at run time.
+
+use anyhow::Result;
+
+use crate::{day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10};
+
+/// Takes the puzzle input and gives the answer as it would be submitted.
+pub type Solver = fn(&str) -> Result<String>;
+
+/// The solver for `part` of `day`, if the day is solved yet.
+pub fn solver(day: u32, part: u32) -> Option<Solver> {
+    let solver: Solver = match (day, part) {
+        (1, 1) => |input| Ok(input.parse::<day_01::Document>()?.solve_1()?.to_string()),
+        (1, 2) => |input| Ok(input.parse::<day_01::Document>()?.solve_2()?.to_string()),
+        (2, 1) => |input| Ok(input.parse::<day_02::Record>()?.solve_1().to_string()),
+        (2, 2) => |input| Ok(input.parse::<day_02::Record>()?.solve_2()?.to_string()),
+        (3, 1) => |input| Ok(input.parse::<day_03::Schematic>()?.solve_1().to_string()),
+        (3, 2) => |input| Ok(input.parse::<day_03::Schematic>()?.solve_2().to_string()),
+        (4, 1) => |input| Ok(input.parse::<day_04::Pile>()?.solve_1()?.to_string()),
+        (4, 2) => |input| Ok(input.parse::<day_04::Pile>()?.solve_2()?.to_string()),
+        (5, 1) => |input| Ok(input.parse::<day_05::Almanac>()?.solve_1()?.to_string()),
+        (5, 2) => |input| Ok(input.parse::<day_05::Almanac>()?.solve_2()?.to_string()),
+        (6, 1) => |input| Ok(input.parse::<day_06::Races>()?.solve_1().to_string()),
+        (6, 2) => |input| Ok(input.parse::<day_06::Race>()?.ways_to_win().to_string()),
+        (7, 1) => |input| {
+            let game: day_07::Game<day_07::Card1> = input.parse()?;
+            Ok(game.winnings().to_string())
+        },
+        (7, 2) => |input| {
+            let game: day_07::Game<day_07::Card2> = input.parse()?;
+            Ok(game.winnings().to_string())
+        },
+        (8, 1) => |input| Ok(input.parse::<day_08::Data>()?.solve_1()?.to_string()),
+        (8, 2) => |input| Ok(input.parse::<day_08::Data>()?.solve_2()?.to_string()),
+        (9, 1) => |input| Ok(input.parse::<day_09::Report>()?.solve_1()?.to_string()),
+        (9, 2) => |input| Ok(input.parse::<day_09::Report>()?.solve_2()?.to_string()),
+        (10, 1) => |input| Ok(input.parse::<day_10::Input>()?.solve_1()?.to_string()),
+        (10, 2) => |input| Ok(input.parse::<day_10::Input>()?.solve_2()?.to_string()),
+        _ => return None,
+    };
+    Some(solver)
+}
@@ -0,0 +1,316 @@
+//! Personal puzzle inputs, which the puzzle author asks not to publish, are
+//! committed encrypted: `problem_1.inp` is kept as `problem_1.inp.enc`, sealed
+//! with ChaCha20-Poly1305 under a key only held locally. [`read`] takes
+//! whichever of the two is there, so the runner and the tests never see the
+//! difference, and fails with [`MissingKey`] when there is no key to open it.
+
+use std::{
+    env,
+    error::Error,
+    fmt,
+    fs::{self, read_dir, read_to_string},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{bail, Context, Result};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Nonce,
+};
+use log::debug;
+
+/// Environment variable holding the key, in hex.
+pub const KEY_VAR: &str = "AOC_INPUT_KEY";
+/// File read for the key when the variable is not set.
+pub const KEY_FILE: &str = ".aoc-input-key";
+
+const EXTENSION: &str = "enc";
+const NONCE_LEN: usize = 12;
+
+#[derive(Clone)]
+pub struct Key(chacha20poly1305::Key);
+
+impl Key {
+    pub fn generate() -> Key {
+        Key(ChaCha20Poly1305::generate_key(&mut OsRng))
+    }
+
+    /// The key from [`KEY_VAR`] or else [`KEY_FILE`], if either is set.
+    pub fn load() -> Result<Option<Key>> {
+        let key = match env::var(KEY_VAR) {
+            Ok(key) => key,
+            Err(_) if Path::new(KEY_FILE).exists() => {
+                read_to_string(KEY_FILE).with_context(|| format!("Reading {KEY_FILE}"))?
+            }
+            Err(_) => return Ok(None),
+        };
+        Ok(Some(key.parse()?))
+    }
+
+    /// Seals `plain` behind a fresh nonce, which leads the result.
+    pub fn encrypt(&self, plain: &str) -> Result<Vec<u8>> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let sealed = ChaCha20Poly1305::new(&self.0)
+            .encrypt(&nonce, plain.as_bytes())
+            .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+        Ok([nonce.as_slice(), &sealed].concat())
+    }
+
+    pub fn decrypt(&self, sealed: &[u8]) -> Result<String> {
+        if sealed.len() < NONCE_LEN {
+            bail!("Too short to be encrypted");
+        }
+        let (nonce, sealed) = sealed.split_at(NONCE_LEN);
+        let plain = ChaCha20Poly1305::new(&self.0)
+            .decrypt(Nonce::from_slice(nonce), sealed)
+            .map_err(|_| anyhow::anyhow!("Wrong key, or the file was altered"))?;
+        Ok(String::from_utf8(plain)?)
+    }
+}
+
+impl FromStr for Key {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let bytes = hex::decode(s.trim()).context("The key should be hex")?;
+        if bytes.len() != 32 {
+            bail!("The key should be 32 bytes, not {}", bytes.len());
+        }
+        Ok(Key(*chacha20poly1305::Key::from_slice(&bytes)))
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+// Never prints the key itself.
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Key(..)")
+    }
+}
+
+/// An input is only there encrypted, and there is no key to read it.
+#[derive(Debug)]
+pub struct MissingKey(pub PathBuf);
+
+impl fmt::Display for MissingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is encrypted, but there is no key in ${KEY_VAR} or {KEY_FILE}",
+            self.0.display()
+        )
+    }
+}
+
+impl Error for MissingKey {}
+
+/// Where `path` is kept encrypted.
+pub fn encrypted(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{EXTENSION}"));
+    PathBuf::from(name)
+}
+
+/// Whether `path` is there, as is or encrypted.
+pub fn exists(path: &Path) -> bool {
+    path.exists() || encrypted(path).exists()
+}
+
+/// Reads `path` as is, or else decrypts it with the key from [`Key::load`].
+pub fn read(path: &Path) -> Result<String> {
+    if path.exists() {
+        return read_with(path, None);
+    }
+    read_with(path, Key::load()?.as_ref())
+}
+
+/// Reads `path` as is, or else decrypts it with `key`.
+pub fn read_with(path: &Path, key: Option<&Key>) -> Result<String> {
+    if path.exists() {
+        return read_to_string(path).with_context(|| format!("Reading {}", path.display()));
+    }
+    let sealed_path = encrypted(path);
+    let sealed = fs::read(&sealed_path).with_context(|| format!("Reading {}", path.display()))?;
+    let Some(key) = key else {
+        return Err(MissingKey(path.to_path_buf()).into());
+    };
+    debug!("Decrypting {}", sealed_path.display());
+    key.decrypt(&sealed)
+        .with_context(|| format!("Decrypting {}", sealed_path.display()))
+}
+
+/// Writes `contents` to `path`, encrypted when there is a key. Returns the
+/// path actually written.
+pub fn write(path: &Path, contents: &str, key: Option<&Key>) -> Result<PathBuf> {
+    let (path, bytes) = match key {
+        Some(key) => (encrypted(path), key.encrypt(contents)?),
+        None => (path.to_path_buf(), contents.as_bytes().to_vec()),
+    };
+    fs::write(&path, bytes).with_context(|| format!("Writing {}", path.display()))?;
+    Ok(path)
+}
+
+/// The personal inputs of the crate at `root`: the real inputs of every day
+/// under `resources/` and everything `cargo aoc` downloaded. These are the
+/// paths as read, whether they are kept encrypted or not.
+pub fn personal_inputs(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut inputs = Vec::new();
+    let resources = root.join("resources");
+    for day in read_dir(&resources).with_context(|| format!("Listing {}", resources.display()))? {
+        let day = day?.path();
+        if day.is_dir() {
+            inputs.extend(listed(&day, |name| {
+                name.starts_with("problem_") && name.ends_with(".inp")
+            })?);
+        }
+    }
+    let downloads = root.join("input/2023");
+    if downloads.is_dir() {
+        inputs.extend(listed(&downloads, |name| name.ends_with(".txt"))?);
+    }
+    inputs.sort();
+    inputs.dedup();
+    Ok(inputs)
+}
+
+// Files of `dir` whose name, less any encrypted extension, is `wanted`.
+fn listed(dir: &Path, wanted: impl Fn(&str) -> bool) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in read_dir(dir).with_context(|| format!("Listing {}", dir.display()))? {
+        let path = entry?.path();
+        let path = match path.extension() {
+            Some(extension) if extension == EXTENSION => path.with_extension(""),
+            _ => path,
+        };
+        if path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(&wanted)
+        {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+/// Replaces `path` by its encrypted version. A link to another input turns
+/// into a link to the encrypted other input.
+pub fn encrypt_file(path: &Path, key: &Key) -> Result<PathBuf> {
+    #[cfg(unix)]
+    if path.is_symlink() {
+        let target = fs::read_link(path)?;
+        let sealed = encrypted(path);
+        std::os::unix::fs::symlink(encrypted(&target), &sealed)
+            .with_context(|| format!("Linking {}", sealed.display()))?;
+        fs::remove_file(path).with_context(|| format!("Removing {}", path.display()))?;
+        return Ok(sealed);
+    }
+    let plain = read_to_string(path).with_context(|| format!("Reading {}", path.display()))?;
+    let written = write(path, &plain, Some(key))?;
+    fs::remove_file(path).with_context(|| format!("Removing {}", path.display()))?;
+    Ok(written)
+}
+
+/// Writes the plain version of `path` next to the encrypted one, for tools
+/// like `cargo aoc` that read inputs directly.
+pub fn decrypt_file(path: &Path, key: &Key) -> Result<()> {
+    let plain = read_with(path, Some(key))?;
+    fs::write(path, plain).with_context(|| format!("Writing {}", path.display()))
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::error::Error;
+    use std::fs::{self, read_to_string};
+
+    use super::{
+        decrypt_file, encrypt_file, encrypted, personal_inputs, read_with, Key, MissingKey,
+    };
+
+    #[test_log::test]
+    fn round_trip() -> Result<(), Box<dyn Error>> {
+        let key = Key::generate();
+        let sealed = key.encrypt("0 3 6 9 12 15\n")?;
+        assert_eq!(key.decrypt(&sealed)?, "0 3 6 9 12 15\n");
+        assert_ne!(key.encrypt("0 3 6 9 12 15\n")?, sealed);
+
+        let error = Key::generate().decrypt(&sealed).unwrap_err();
+        assert_eq!(error.to_string(), "Wrong key, or the file was altered");
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn keys() -> Result<(), Box<dyn Error>> {
+        let key = Key::generate();
+        let text = key.to_string();
+        assert_eq!(text.len(), 64);
+        assert_eq!(text.parse::<Key>()?.to_string(), text);
+        assert_eq!(format!("{key:?}"), "Key(..)");
+        assert!("abcd".parse::<Key>().is_err());
+        assert!("not hex".parse::<Key>().is_err());
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn files() -> Result<(), Box<dyn Error>> {
+        let key = Key::generate();
+        let root = tempfile::tempdir()?;
+        let day = root.path().join("resources/day_09");
+        fs::create_dir_all(&day)?;
+        fs::create_dir_all(root.path().join("input/2023"))?;
+        for name in ["train_problem_1.inp", "problem_1.inp", "problem_2.inp"] {
+            fs::write(day.join(name), "10 13 16 21 30 45\n")?;
+        }
+        fs::write(root.path().join("input/2023/day9.txt"), "1 2 3\n")?;
+
+        let inputs = personal_inputs(root.path())?;
+        let expected = vec![
+            root.path().join("input/2023/day9.txt"),
+            day.join("problem_1.inp"),
+            day.join("problem_2.inp"),
+        ];
+        assert_eq!(inputs, expected);
+
+        let path = day.join("problem_1.inp");
+        encrypt_file(&path, &key)?;
+        assert!(!path.exists());
+        assert!(encrypted(&path).exists());
+        assert_eq!(personal_inputs(root.path())?, expected);
+        assert_eq!(read_with(&path, Some(&key))?, "10 13 16 21 30 45\n");
+
+        let error = read_with(&path, None).unwrap_err();
+        assert!(error.downcast_ref::<MissingKey>().is_some());
+
+        decrypt_file(&path, &key)?;
+        assert_eq!(read_to_string(&path)?, "10 13 16 21 30 45\n");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test_log::test]
+    fn links() -> Result<(), Box<dyn Error>> {
+        let key = Key::generate();
+        let root = tempfile::tempdir()?;
+        let first = root.path().join("problem_1.inp");
+        let second = root.path().join("problem_2.inp");
+        fs::write(&first, "0 3 6 9 12 15\n")?;
+        std::os::unix::fs::symlink("problem_1.inp", &second)?;
+
+        encrypt_file(&first, &key)?;
+        encrypt_file(&second, &key)?;
+        assert!(!second.is_symlink());
+        assert_eq!(
+            fs::read_link(encrypted(&second))?,
+            encrypted("problem_1.inp".as_ref())
+        );
+        assert_eq!(read_with(&second, Some(&key))?, "0 3 6 9 12 15\n");
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+//! Line by line solving over any reader, so inputs never have to be held in
+//! memory at once.
+
+use std::io::BufRead;
+
+use anyhow::{Context, Result};
+
+/// Folds `f` over every line of `reader`, reusing a single buffer. Line
+/// endings are stripped as with [`str::lines`] and errors are tagged with the
+/// line, counted from 1, they happened on.
+pub fn try_fold_lines<R, T, F>(mut reader: R, init: T, mut f: F) -> Result<T>
+where
+    R: BufRead,
+    F: FnMut(T, &str) -> Result<T>,
+{
+    let mut buffer = String::new();
+    let mut acc = init;
+    for number in 1.. {
+        buffer.clear();
+        let read = reader
+            .read_line(&mut buffer)
+            .with_context(|| format!("Reading line {number}"))?;
+        if read == 0 {
+            break;
+        }
+
+        let line = buffer.strip_suffix('\n').unwrap_or(&buffer);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        acc = f(acc, line).with_context(|| format!("Line {number}"))?;
+    }
+    Ok(acc)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::try_fold_lines;
+    use anyhow::bail;
+
+    #[test_log::test]
+    fn lines_match_str_lines() {
+        for input in ["", "a", "a\n", "a\r\nb\n\nc", "a\n\r\n"] {
+            let lines = try_fold_lines(input.as_bytes(), vec![], |mut lines, line| {
+                lines.push(line.to_string());
+                Ok(lines)
+            })
+            .unwrap();
+            assert_eq!(lines, input.lines().collect::<Vec<_>>());
+        }
+    }
+
+    #[test_log::test]
+    fn errors_carry_line_number() {
+        let error = try_fold_lines("1\n2\nx\n4".as_bytes(), 0, |sum, line| {
+            if line == "x" {
+                bail!("not a number");
+            }
+            Ok(sum + line.parse::<usize>()?)
+        })
+        .unwrap_err();
+        assert_eq!(format!("{error:#}"), "Line 3: not a number");
+    }
+}